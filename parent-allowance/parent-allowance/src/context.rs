@@ -1,4 +1,4 @@
-use soroban_sdk::{AccountId, contracttype};
+use soroban_sdk::{AccountId, Bytes, BytesN, contracttype, Vec};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
@@ -7,17 +7,286 @@ pub enum State {
     Initiated,
     Started,
     Finished,
+    Paused,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Schedule {
+    pub start: u64,
+    pub step: u64,
+    pub end: u64,
+    pub amount: i128,
+    pub calendar: Calendar,
+}
+
+// How the steps of a schedule are laid out. Calendar steps fall at midnight UTC,
+// on a day of every month (1 to 31, the last day for shorter months) or on a
+// weekday of every week (1 for Monday to 7 for Sunday), ignoring the step period.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum Calendar {
+    Seconds,
+    Monthly(u32),
+    Weekly(u32),
+}
+
+// When each step is paid: at the end of its period, or at its start
+// so the first step is available as soon as the schedule starts
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum Payout {
+    End,
+    Start,
+}
+
+// The allowance accrued up to 'timestamp'. 'fraction' is the part of a unit the unspent
+// balance holds on top of it while earning interest, in fixed point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Checkpoint {
+    pub accrued: i128,
+    pub timestamp: u64,
+    pub fraction: i128,
+}
+
+// A period in which accrual was frozen. 'end' stays 0 while it's still ongoing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Pause {
+    pub start: u64,
+    pub end: u64,
+}
+
+// Guardrails on how a child spends its allowance in a token. 'per_draw' caps each
+// withdraw, 'per_window' caps the total withdrawn within any rolling 'window' of
+// seconds and 'interval' is the minimum of seconds between withdraws. '0' disables each.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Limits {
+    pub per_draw: i128,
+    pub window: u64,
+    pub per_window: i128,
+    pub interval: u64,
+}
+
+// How much of the unclaimed allowance carries over to the next steps. Anything
+// above it is forfeited. 'Amount' caps the balance, 'Steps' keeps at most that
+// many steps worth of allowance and 'None' only keeps the current step's.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum Carryover {
+    Unlimited,
+    Amount(i128),
+    Steps(u64),
+    None,
+}
+
+// A withdraw made by a child, kept to enforce its spending limits
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Draw {
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum ReqStatus {
+    Pending,
+    Approved,
+    Advanced,
+    Rejected,
+}
+
+// A withdraw filed by a child for the parent to approve. 'Advanced' requests
+// were paid beyond the available allowance, to be recouped from future steps.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Request {
+    pub child: AccountId,
+    pub token: BytesN<32>,
+    pub amount: i128,
+    pub memo: Bytes,
+    pub status: ReqStatus,
+    pub timestamp: u64,
+}
+
+// A one-off task assigned to a child, rewarded on top of its allowance once done
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Chore {
+    pub id: u32,
+    pub child: AccountId,
+    pub token: BytesN<32>,
+    pub reward: i128,
+    pub memo: Bytes,
+    pub done: bool,
+}
+
+// Savings a child locks out of its allowance towards a target. It's released once
+// the target or the unlock timestamp ('0' for none) is reached, along with the
+// parent's match of 'match_bps' basis points over what was saved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Goal {
+    pub id: u32,
+    pub child: AccountId,
+    pub token: BytesN<32>,
+    pub memo: Bytes,
+    pub target: i128,
+    pub unlock: u64,
+    pub saved: i128,
+    pub match_bps: u32,
+    pub released: bool,
+}
+
+// The allowance available for a child and whether the funds backing it,
+// in escrow or approved by the parent, cover what is owed to all children
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Available {
+    pub amount: i128,
+    pub covered: bool,
+}
+
+// What is owed to all children in a token against the funds backing it:
+// the parent's approval to the contract, the parent's balance and the escrow
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Funding {
+    pub owed: i128,
+    pub approved: i128,
+    pub balance: i128,
+    pub escrowed: i128,
+}
+
+// Everything about a child's allowance in a token, in a single view
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct ChildSumm {
+    pub child: AccountId,
+    pub token: BytesN<32>,
+    pub rate: i128,
+    pub withdrawn: i128,
+    pub available: i128,
+    pub schedule: Schedule,
+}
+
+// Allowances are kept for each child in each token
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct ChildToken {
+    pub child: AccountId,
+    pub token: BytesN<32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct ChildAllow {
+    pub child: AccountId,
+    pub token: BytesN<32>,
+    pub amount: i128,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct ChildSched {
+    pub child: AccountId,
+    pub token: BytesN<32>,
+    pub schedule: Schedule,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct ChildCarry {
+    pub child: AccountId,
+    pub token: BytesN<32>,
+    pub carryover: Carryover,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct ChildRate {
+    pub child: AccountId,
+    pub token: BytesN<32>,
+    pub rate_bps: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct GoalMatch {
+    pub id: u32,
+    pub match_bps: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Guardians {
+    pub accounts: Vec<AccountId>,
+    pub threshold: u32,
+}
+
+// A pending admin handover, which must be accepted by the new admin until 'expiration'
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct AdminProp {
+    pub admin: AccountId,
+    pub expiration: u64,
+}
+
+// Sensitive actions, which only run once approved by the threshold of guardians
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum Action {
+    SetAllow(ChildAllow),
+    SetAdvnc(ChildAllow),
+    SetIntrst(ChildRate),
+    SetSched(ChildSched),
+    SetCarry(ChildCarry),
+    SetClaim(u64),
+    RmChild(AccountId),
+    SetGuards(Guardians),
+    DoneChore(u32),
+    MatchGoal(GoalMatch),
 }
 
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
     Admin,      // AccountId
-    Allowance(AccountId),  // i128
-    WithdAllow(AccountId), // i128
+    PendAdmin,  // AdminProp
+    Allowance(ChildToken),  // i128
+    WithdAllow(ChildToken), // i128
+    Nonce(AccountId),      // i128
+    Schedule(ChildToken),   // Schedule
+    Checkpnt(ChildToken),   // Checkpoint
+    Suspends(AccountId),   // Vec<Pause>
+    Limits(ChildToken),     // Limits
+    Draws(ChildToken),      // Vec<Draw>
+    Carryover(ChildToken),  // Carryover
+    AdvLimit(ChildToken),   // i128
+    Interest(ChildToken),   // u32
+    Stream(ChildToken),     // bool
+    Payout(ChildToken),     // Payout
+    Credits(ChildToken),    // i128
     StpPeriod,  // u64
     StrtPeriod,  // u64
     EndPeriod,  // u64
-    TokenAddr,  // BytesN
+    ClaimWndw,  // u64
+    Tokens,     // Vec<BytesN>
+    Children,   // Vec<AccountId>
+    Escrow,     // bool
     State,      // enum State
+    Pauses,     // Vec<Pause>
+    Guardians,  // Guardians
+    Approvals(Action), // Vec<AccountId>
+    ReqCount,   // u32
+    Request(u32),      // Request
+    ChoreCount, // u32
+    Chore(u32),        // Chore
+    Chores(AccountId), // Vec<u32>
+    GoalCount,  // u32
+    Goal(u32),         // Goal
+    Goals(AccountId),  // Vec<u32>
 }
\ No newline at end of file
@@ -1,9 +1,12 @@
-use crate::context::State;
+use crate::context::{
+    Action, AdminProp, Available, Calendar, Carryover, ChildAllow, ChildCarry, ChildRate, Chore, Goal, GoalMatch, ChildSched, ChildSumm, Funding, Guardians, Limits,
+    Payout, ReqStatus, Request, Schedule, State,
+};
 use crate::errors::Error;
 use crate::services::*;
 
-use soroban_auth::{Identifier, Signature};
-use soroban_sdk::{contractimpl, panic_with_error, AccountId, Address, BytesN, Env};
+use soroban_auth::{verify, Identifier, Signature};
+use soroban_sdk::{contractimpl, symbol, AccountId, Address, Bytes, BytesN, Env, Vec};
 
 pub mod token {
     soroban_sdk::contractimport!(file = "../soroban_token_contract.wasm");
@@ -17,37 +20,445 @@ pub trait ParentAllowanceTrait {
         step_period: u64, //The interval in seconds for each allowance to be available. They stack up over time.
         start_period: u64, //The exact timestamp to when the allowance starts to be accrued. '0' stats right away.
         end_period: u64, //The exact timestamp to when the allowance stops to be accrued. '0' runs indefinitely.
-    );
+    ) -> Result<(), Error>;
+
+    // Proposes handing the admin role over to a new account, which must accept it
+    // up until the expiration timestamp. Only the current admin can propose it.
+    fn prop_admin(env: Env, new_admin: AccountId, expiration: u64) -> Result<(), Error>;
+
+    // Accepts a pending admin handover. Must be invoked by the proposed admin.
+    fn acpt_admin(env: Env) -> Result<(), Error>;
+
+    // Get the current admin
+    fn get_admin(env: Env) -> Result<AccountId, Error>;
+
+    // Get the pending admin handover
+    fn get_padmin(env: Env) -> Result<AdminProp, Error>;
+
+    // Defines the guardians allowed to manage the contract and how many of them must approve
+    // sensitive actions (changing allowances, advance limits, schedules, children and the guardians themselves).
+    // Any single guardian can run routine actions. Requires the approval threshold.
+    fn set_guards(env: Env, guardians: Vec<AccountId>, threshold: u32) -> Result<(), Error>;
+
+    // Get the current guardians
+    fn get_guards(env: Env) -> Result<Guardians, Error>;
+
+    // Adds a token to the whitelist of tokens allowances can be paid in
+    fn add_token(env: Env, token: BytesN<32>) -> Result<(), Error>;
+
+    // Removes a token from the whitelist, so it can't be paid in anymore
+    fn rm_token(env: Env, token: BytesN<32>) -> Result<(), Error>;
+
+    // Get the whitelist of tokens allowances can be paid in
+    fn get_tokens(env: Env) -> Result<Vec<BytesN<32>>, Error>;
+
+    // Defines an allowance amount in a token for a specific child account to be accrued at
+    // each step_period. Requires the approval threshold.
+    fn set_allow(env: Env, child_account: AccountId, token: BytesN<32>, allowance: i128) -> Result<(), Error>;
+
+    // Check the current allowance in a token for a child account
+    fn get_allow(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<i128, Error>;
+
+    // Defines how much a specific child account can withdraw in a token beyond the allowance
+    // available, to be paid back by the allowance accrued next. Requires the approval threshold.
+    fn set_advnc(env: Env, child_account: AccountId, token: BytesN<32>, limit: i128) -> Result<(), Error>;
+
+    // Get how much a child account can withdraw in advance in a token
+    fn get_advnc(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<i128, Error>;
+
+    // Defines the interest, in basis points, the unspent allowance of a specific child
+    // account in a token earns at each step (e.g. 100 adds 1% per step), rounded down.
+    // Requires the approval threshold.
+    fn set_intrst(env: Env, child_account: AccountId, token: BytesN<32>, interest_bps: u32) -> Result<(), Error>;
+
+    // Get the interest the unspent allowance of a child account earns in a token
+    fn get_intrst(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<u32, Error>;
 
-    // Defines an allowance amount for a specific child account to be accrued at each step_period
-    fn set_allow(env: Env, child_account: AccountId, allowance: i128);
+    // Defines if the allowance of a specific child account in a token streams, prorating
+    // each step per second so part of it can be withdrawn before it's completed
+    fn set_stream(env: Env, child_account: AccountId, token: BytesN<32>, streaming: bool) -> Result<(), Error>;
+
+    // Get if the allowance of a child account in a token streams
+    fn get_stream(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<bool, Error>;
+
+    // Defines when each step of a specific child account in a token is paid: at the end of
+    // its period (the default), or at its start so the first step is available right away
+    fn set_payout(env: Env, child_account: AccountId, token: BytesN<32>, payout: Payout) -> Result<(), Error>;
+
+    // Get when each step of a child account in a token is paid
+    fn get_payout(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<Payout, Error>;
+
+    // Get how much a child account withdrew in advance in a token and didn't pay back yet
+    fn get_debt(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<i128, Error>;
+
+    // Removes a child account, discarding its allowances and any amount not yet withdrawn.
+    // Requires the approval threshold.
+    fn rm_child(env: Env, child_account: AccountId) -> Result<(), Error>;
+
+    // Get every registered child account
+    fn list_child(env: Env) -> Result<Vec<AccountId>, Error>;
+
+    // Get the rate, withdrawn total, available amount and schedule of every registered
+    // child account in each token it is paid in
+    fn summary(env: Env) -> Result<Vec<ChildSumm>, Error>;
+
+    // Freezes the accrual and withdrawals of a specific child account
+    fn suspend(env: Env, child_account: AccountId) -> Result<(), Error>;
+
+    // Lets a suspended child account accrue and withdraw again
+    fn unsuspend(env: Env, child_account: AccountId) -> Result<(), Error>;
+
+    // Check if a child account is currently suspended
+    fn get_susp(env: Env, child_account: AccountId) -> Result<bool, Error>;
+
+    // Freezes the accrual and withdrawals of every child account
+    fn pause(env: Env) -> Result<(), Error>;
+
+    // Lets every child account accrue and withdraw again after a pause
+    fn resume(env: Env) -> Result<(), Error>;
+
+    // Get the current state of the contract
+    fn get_state(env: Env) -> Result<State, Error>;
+
+    // Defines a schedule of its own for a specific child account in a token, replacing the
+    // contract defaults. The periods follow the same rules as in `initialize`, unless the
+    // calendar lays the steps on a day of every month or week instead.
+    // Requires the approval threshold.
+    #[allow(clippy::too_many_arguments)]
+    fn set_sched(
+        env: Env,
+        child_account: AccountId,
+        token: BytesN<32>,
+        step_period: u64,
+        start_period: u64,
+        end_period: u64,
+        allowance: i128,
+        calendar: Calendar,
+    ) -> Result<(), Error>;
 
-    // Check the current allowance for a child account
-    fn get_allow(env: Env, child_account: AccountId) -> i128;
+    // Get the schedule followed by a child account in a token, its own or the contract defaults
+    fn get_sched(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<Schedule, Error>;
 
-    // Get the amount of allowance already withdrawn by a given child account
-    fn get_wthdr(env: Env, child_account: AccountId) -> i128;
+    // Get the amount of allowance in a token already withdrawn by a given child account
+    fn get_wthdr(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<i128, Error>;
 
-    // Get the amount of allowance available for a given child account
-    fn get_aval(env: Env, child_account: AccountId) -> i128;
+    // Get the amount of allowance in a token available for a given child account, and
+    // whether the funds backing the allowances cover what is available to all children
+    fn get_aval(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<Available, Error>;
 
     // Get the start_period
-    fn get_start(env: Env) -> u64;
+    fn get_start(env: Env) -> Result<u64, Error>;
 
     // Get the step_period
-    fn get_step(env: Env) -> u64;
+    fn get_step(env: Env) -> Result<u64, Error>;
 
     // Get the end_period
-    fn get_end(env: Env) -> u64;
+    fn get_end(env: Env) -> Result<u64, Error>;
+
+    // Defines for how many seconds after the end_period the accrued allowance can still
+    // be withdrawn. '0' keeps it available indefinitely. Requires the approval threshold.
+    fn set_claim(env: Env, claim_window: u64) -> Result<(), Error>;
+
+    // Get the claim window after the end_period
+    fn get_claim(env: Env) -> Result<u64, Error>;
+
+    // Defines the spending limits of a specific child account in a token: the maximum per
+    // withdraw, the maximum total within a rolling window of seconds and the minimum of
+    // seconds between withdraws. '0' disables each of them.
+    fn set_limits(
+        env: Env,
+        child_account: AccountId,
+        token: BytesN<32>,
+        per_draw: i128,
+        window: u64,
+        per_window: i128,
+        interval: u64,
+    ) -> Result<(), Error>;
+
+    // Get the spending limits of a child account in a token
+    fn get_limits(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<Limits, Error>;
+
+    // Defines how much of the unclaimed allowance of a specific child account in a token
+    // carries over to the next steps: all of it, up to an amount, up to a number of steps
+    // worth of allowance or nothing but the current step's. The rest is forfeited.
+    // Requires the approval threshold.
+    fn set_carry(
+        env: Env,
+        child_account: AccountId,
+        token: BytesN<32>,
+        carryover: Carryover,
+    ) -> Result<(), Error>;
+
+    // Get the carryover policy of a child account in a token
+    fn get_carry(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<Carryover, Error>;
+
+    // Enables or disables the escrow mode. In escrow mode the allowances are paid out of
+    // funds deposited in the contract, otherwise they're pulled from the admin on withdraw.
+    fn set_escrow(env: Env, enabled: bool) -> Result<(), Error>;
+
+    // Check if the escrow mode is enabled
+    fn get_escrow(env: Env) -> Result<bool, Error>;
+
+    // Deposits an amount of a token from the admin into the contract, to be paid out
+    // in escrow mode. The admin must have approved the contract to transfer it.
+    fn deposit(env: Env, token: BytesN<32>, amount: i128) -> Result<(), Error>;
+
+    // Returns an amount of a token held by the contract to the admin. In escrow mode,
+    // what is still owed to the children can't be reclaimed.
+    fn reclaim(env: Env, token: BytesN<32>, amount: i128) -> Result<(), Error>;
+
+    // Get how much of a token is owed to all children against what the parent has
+    // approved to the contract, the parent's balance and the funds held in escrow
+    fn fund_stat(env: Env, token: BytesN<32>) -> Result<Funding, Error>;
+
+    // Get the nonce a given child account must use on its next signed withdraw
+    fn get_nonce(env: Env, child_account: AccountId) -> Result<i128, Error>;
+
+    // Withdraws an amount of allowance in a token to a given child account if available.
+    // The child must authorize it, either as the invoker (nonce 0) or through
+    // a signature using the current nonce from get_nonce.
+    fn withdraw(
+        env: Env,
+        child_sig: Signature,
+        nonce: i128,
+        child_account: AccountId,
+        token: BytesN<32>,
+        draw_amount: i128,
+    ) -> Result<(), Error>;
+
+    // Files a request for the parent to pay a given child account an amount in a token,
+    // with a short memo on what it's for. Returns the request id. Authorized the same
+    // way as `withdraw`, sharing its nonce.
+    fn request(
+        env: Env,
+        child_sig: Signature,
+        nonce: i128,
+        child_account: AccountId,
+        token: BytesN<32>,
+        amount: i128,
+        memo: Bytes,
+    ) -> Result<u32, Error>;
+
+    // Approves a pending request, paying it right away. Unless it's approved as an advance,
+    // the amount must be within the allowance available. Advances are recouped from the
+    // allowance accrued from then on. Only the admin can approve it.
+    fn aprv_req(env: Env, id: u32, advance: bool) -> Result<(), Error>;
+
+    // Rejects a pending request. Only the admin can reject it.
+    fn rjct_req(env: Env, id: u32) -> Result<(), Error>;
+
+    // Get a request filed by a child account
+    fn get_req(env: Env, id: u32) -> Result<Request, Error>;
+
+    // Assigns a chore to a specific child account, with a short memo on what it's about and
+    // a reward in a token paid on top of its allowance once done. Returns the chore id.
+    fn add_chore(
+        env: Env,
+        child_account: AccountId,
+        token: BytesN<32>,
+        reward: i128,
+        memo: Bytes,
+    ) -> Result<u32, Error>;
+
+    // Marks a chore as done, crediting its reward to the allowance available to the child.
+    // Requires the approval threshold.
+    fn done_chore(env: Env, id: u32) -> Result<(), Error>;
+
+    // Get a chore
+    fn get_chore(env: Env, id: u32) -> Result<Chore, Error>;
+
+    // Get every chore assigned to a child account
+    fn get_chores(env: Env, child_account: AccountId) -> Result<Vec<Chore>, Error>;
+
+    // Creates a savings goal for a child account in a token, with a short memo on what it's
+    // for, a target amount and an unlock timestamp ('0' for none). Returns the goal id.
+    // Authorized the same way as `withdraw`, sharing its nonce.
+    #[allow(clippy::too_many_arguments)]
+    fn add_goal(
+        env: Env,
+        child_sig: Signature,
+        nonce: i128,
+        child_account: AccountId,
+        token: BytesN<32>,
+        memo: Bytes,
+        target: i128,
+        unlock: u64,
+    ) -> Result<u32, Error>;
+
+    // Locks an amount of the allowance available to a child account into one of its goals.
+    // Authorized the same way as `withdraw`, sharing its nonce.
+    fn save_goal(
+        env: Env,
+        child_sig: Signature,
+        nonce: i128,
+        id: u32,
+        amount: i128,
+    ) -> Result<(), Error>;
 
-    // Withdraws an amount of allowance to a given child account if available
-    fn withdraw(env: Env, child_account: AccountId, draw_amount: i128) -> Result<(), Error>;
+    // Defines how much the parent tops up what is saved in a goal, in basis points
+    // (e.g. 5000 adds 50%) up to doubling it, paid out when it's released.
+    // Requires the approval threshold.
+    fn match_goal(env: Env, id: u32, match_bps: u32) -> Result<(), Error>;
+
+    // Pays a goal out to its child, along with the parent's match, once it reached its
+    // target or unlock timestamp. What was saved counts towards the spending limits.
+    // Authorized the same way as `withdraw`, sharing its nonce.
+    fn wthdr_goal(env: Env, child_sig: Signature, nonce: i128, id: u32) -> Result<(), Error>;
+
+    // Get a savings goal
+    fn get_goal(env: Env, id: u32) -> Result<Goal, Error>;
+
+    // Get every savings goal of a child account
+    fn get_goals(env: Env, child_account: AccountId) -> Result<Vec<Goal>, Error>;
 }
 
+const MAX_MEMO_LEN: u32 = 32;
+const MAX_MATCH_BPS: u32 = 10000;
+
 pub struct ParentAllowance;
 
-//TODO
-// add return result / error
+// Makes sure the contract was initialized
+fn check_initialized(env: &Env) -> Result<(), Error> {
+    if read_state(env) == State::NotInititd {
+        return Err(Error::NotInitialized);
+    }
+    Ok(())
+}
+
+// Makes sure the invoker is one of the guardians, returning its account
+fn check_guardian(env: &Env) -> Result<AccountId, Error> {
+    check_initialized(env)?;
+    read_invoker_guardian(env)
+}
+
+// Makes sure the invoker is the admin, returning its account
+fn check_admin(env: &Env) -> Result<AccountId, Error> {
+    check_initialized(env)?;
+    let admin = read_admin(env);
+    if env.invoker() != Address::Account(admin.clone()) {
+        return Err(Error::NotAuthorized);
+    }
+    Ok(admin)
+}
+
+// Makes sure the token is in the whitelist
+fn check_token(env: &Env, token: &BytesN<32>) -> Result<(), Error> {
+    if !is_token_allowed(env, token) {
+        return Err(Error::TokenNotAllowed);
+    }
+    Ok(())
+}
+
+// Makes sure the child account has an allowance set in the token
+fn check_child(env: &Env, child_account: AccountId, token: BytesN<32>) -> Result<(), Error> {
+    check_initialized(env)?;
+    if !has_allowance(env, child_account, token) {
+        return Err(Error::ChildNotSet);
+    }
+    Ok(())
+}
+
+// Makes sure the child account authorized the call through the signature. It must
+// belong to a user account and use the child's current nonce, which is then consumed.
+fn check_child_sig(
+    env: &Env,
+    child_sig: &Signature,
+    nonce: i128,
+    child_account: AccountId,
+) -> Result<(), Error> {
+    let signer = read_signer_account(env, child_sig)?;
+    if signer != child_account {
+        return Err(Error::InvalidInvoker);
+    }
+    verify_and_consume_nonce(env, child_sig, child_account, nonce)
+}
+
+// Makes sure an amount to be moved is above zero
+fn check_amount(amount: i128) -> Result<(), Error> {
+    if amount <= 0 {
+        return Err(Error::NegativeAmount);
+    }
+    Ok(())
+}
+
+// Makes sure a schedule has steps and, unless it runs indefinitely ('0'),
+// ends after it starts. Calendar steps must fall on an actual day.
+fn check_period(
+    calendar: &Calendar,
+    step_period: u64,
+    start_period: u64,
+    end_period: u64,
+) -> Result<(), Error> {
+    let has_steps = match calendar {
+        Calendar::Seconds => step_period > 0,
+        Calendar::Monthly(day) => (1..=31).contains(day),
+        Calendar::Weekly(weekday) => (1..=7).contains(weekday),
+    };
+    if !has_steps || (end_period > 0 && end_period <= start_period) {
+        return Err(Error::InvalidPeriod);
+    }
+    Ok(())
+}
+
+// Makes sure the goal exists and wasn't released yet, returning it
+fn check_goal(env: &Env, id: u32) -> Result<Goal, Error> {
+    if !has_goal(env, id) {
+        return Err(Error::GoalNotFound);
+    }
+
+    let goal = read_goal(env, id);
+    if goal.released {
+        return Err(Error::GoalReleased);
+    }
+    Ok(goal)
+}
+
+// Makes sure the child account can move its allowance, with neither the
+// contract paused nor the child suspended
+fn check_not_frozen(env: &Env, child_account: AccountId) -> Result<(), Error> {
+    if read_state(env) == State::Paused {
+        return Err(Error::ContractPaused);
+    }
+    if is_suspended(env, child_account) {
+        return Err(Error::ChildSuspended);
+    }
+    Ok(())
+}
+
+// Makes sure the request exists and is still pending, returning it
+fn check_request(env: &Env, id: u32) -> Result<Request, Error> {
+    if !has_request(env, id) {
+        return Err(Error::RequestNotFound);
+    }
+
+    let request = read_request(env, id);
+    if request.status != ReqStatus::Pending {
+        return Err(Error::RequestNotPending);
+    }
+    Ok(request)
+}
+
+// Moves the contract to started once an allowance is set up, unless it's paused
+fn start_contract(env: &Env) {
+    if read_state(env) == State::Initiated && !read_children(env).is_empty() {
+        write_state(env, State::Started);
+    }
+}
+
+// Records the guardian approval for a sensitive action, returning true
+// once it has enough approvals to run
+fn approve(env: &Env, guardian: AccountId, action: Action) -> bool {
+    if approve_action(env, guardian.clone(), action.clone()) {
+        return true;
+    }
+
+    env.events().publish(
+        (symbol!("allow"), symbol!("approve"), guardian),
+        (action, env.ledger().timestamp()),
+    );
+    false
+}
+
 #[contractimpl]
 impl ParentAllowanceTrait for ParentAllowance {
     fn initialize(
@@ -57,143 +468,1178 @@ impl ParentAllowanceTrait for ParentAllowance {
         step_period: u64,
         start_period: u64,
         end_period: u64,
-    ) {
+    ) -> Result<(), Error> {
         if read_state(&env) != State::NotInititd {
-            panic_with_error!(&env, Error::AlreadyInitialized);
+            return Err(Error::AlreadyInitialized);
         }
 
+        //when start_period is set as 0, the allowance distribution starts right away
+        //otherwise, it is programmed to start at the informed timestamp
+        let start_period = if start_period == 0 {
+            env.ledger().timestamp()
+        } else {
+            start_period
+        };
+
         // The step_period defines the interval for each withdraw to be performed.
         // Setting as 0 would cause a division by 0 so it is not accepted.
-        if step_period == 0 {
-            panic_with_error!(&env, Error::InvalidArguments);
-        }
+        check_period(&Calendar::Seconds, step_period, start_period, end_period)?;
 
         write_state(&env, State::Initiated);
-        write_admin(&env, admin);
-        write_token_address(&env, token_address);
+        write_admin(&env, admin.clone());
+
+        //the admin starts as the only guardian
+        let mut accounts = Vec::new(&env);
+        accounts.push_back(admin.clone());
+        write_guardians(
+            &env,
+            Guardians {
+                accounts,
+                threshold: 1,
+            },
+        );
+
+        //the initial token starts as the only one in the whitelist
+        let mut tokens = Vec::new(&env);
+        tokens.push_back(token_address.clone());
+        write_tokens(&env, tokens);
         write_step_period(&env, step_period);
+        write_claim_window(&env, 0);
 
         //stores the end_period. When set to 0, there is no final date and the contract just keeps on going.
         write_end_period(&env, end_period);
 
-        //when start_period is set as 0, the allowance distribution starts right away
-        //otherwise, it is programmed to start at the informed timestamp
-        if start_period == 0 {
-            write_start_period(&env, env.ledger().timestamp());
-        } else {
-            write_start_period(&env, start_period);
+        write_start_period(&env, start_period);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("init")),
+            (
+                admin,
+                token_address,
+                step_period,
+                start_period,
+                end_period,
+            ),
+        );
+
+        Ok(())
+    }
+
+    fn prop_admin(env: Env, new_admin: AccountId, expiration: u64) -> Result<(), Error> {
+        check_admin(&env)?;
+
+        if expiration <= env.ledger().timestamp() {
+            return Err(Error::InvalidArguments);
         }
+
+        let proposal = AdminProp {
+            admin: new_admin,
+            expiration,
+        };
+        write_admin_proposal(&env, proposal.clone());
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("propadmin")),
+            (proposal, env.ledger().timestamp()),
+        );
+
+        Ok(())
     }
 
-    fn set_allow(env: Env, child_account: AccountId, allowance: i128) {
-        if env.invoker() != Address::Account(read_admin(&env)) {
-            panic_with_error!(&env, Error::InvalidInvoker);
+    fn acpt_admin(env: Env) -> Result<(), Error> {
+        check_initialized(&env)?;
+        if !has_admin_proposal(&env) {
+            return Err(Error::NoAdminProposal);
+        }
+
+        let proposal = read_admin_proposal(&env);
+        if env.invoker() != Address::Account(proposal.admin.clone()) {
+            return Err(Error::NotAuthorized);
+        }
+
+        if env.ledger().timestamp() > proposal.expiration {
+            return Err(Error::AdminProposalExpired);
         }
-        write_allowance(&env, child_account, allowance);
+
+        let previous_admin = read_admin(&env);
+        transfer_admin(&env, proposal.admin.clone());
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("admin")),
+            (previous_admin, proposal.admin, env.ledger().timestamp()),
+        );
+
+        Ok(())
     }
 
-    fn get_allow(env: Env, child_account: AccountId) -> i128 {
-        read_allowance(&env, child_account)
+    fn get_admin(env: Env) -> Result<AccountId, Error> {
+        check_initialized(&env)?;
+        Ok(read_admin(&env))
     }
 
-    fn get_start(env: Env) -> u64 {
-        read_start_period(&env)
+    fn get_padmin(env: Env) -> Result<AdminProp, Error> {
+        check_initialized(&env)?;
+        if !has_admin_proposal(&env) {
+            return Err(Error::NoAdminProposal);
+        }
+        Ok(read_admin_proposal(&env))
     }
 
-    fn get_step(env: Env) -> u64 {
-        read_step_period(&env)
+    fn set_guards(env: Env, guardians: Vec<AccountId>, threshold: u32) -> Result<(), Error> {
+        let guardian = check_guardian(&env)?;
+
+        // There must always be someone able to manage the contract
+        if guardians.is_empty() || threshold == 0 || threshold > guardians.len() {
+            return Err(Error::InvalidArguments);
+        }
+
+        let guardians = Guardians {
+            accounts: guardians,
+            threshold,
+        };
+        if !approve(&env, guardian, Action::SetGuards(guardians.clone())) {
+            return Ok(());
+        }
+        write_guardians(&env, guardians.clone());
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("guards")),
+            (guardians, env.ledger().timestamp()),
+        );
+
+        Ok(())
     }
 
-    fn get_end(env: Env) -> u64 {
-        read_end_period(&env)
+    fn get_guards(env: Env) -> Result<Guardians, Error> {
+        check_initialized(&env)?;
+        Ok(read_guardians(&env))
     }
 
-    fn get_aval(env: Env, child_account: AccountId) -> i128 {
-        let start_period = read_start_period(&env);
-        let step_period = read_step_period(&env);
-        let child_allowance = read_allowance(&env, child_account.clone());
-        let withdrawn_allowance = read_withdrawn_allowance(&env, child_account.clone());
+    fn add_token(env: Env, token: BytesN<32>) -> Result<(), Error> {
+        check_guardian(&env)?;
 
-        return calculate_allowance_available(
-            &env,
-            start_period,
-            step_period,
-            child_allowance,
-            withdrawn_allowance,
+        let mut tokens = read_tokens(&env);
+        if tokens.contains(&token) {
+            return Err(Error::InvalidArguments);
+        }
+        tokens.push_back(token.clone());
+        write_tokens(&env, tokens);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("addtoken")),
+            (token, env.ledger().timestamp()),
         );
-    }
 
-    fn get_wthdr(env: Env, child_account: AccountId) -> i128 {
-        read_withdrawn_allowance(&env, child_account)
+        Ok(())
     }
 
-    //TODO:
-    // use invoker as child_account
-    fn withdraw(env: Env, child_account: AccountId, draw_amount: i128) -> Result<(), Error> {
-        // This is a simple check to ensure the `withdraw` function has not been
-        // invoked by a contract. For our purposes, it *must* be invoked by a
-        // user account.
-        match env.invoker() {
-            Address::Account(id) => id,
-            _ => panic_with_error!(&env, Error::InvalidInvoker),
+    fn rm_token(env: Env, token: BytesN<32>) -> Result<(), Error> {
+        check_guardian(&env)?;
+
+        let mut tokens = read_tokens(&env);
+        let index = match tokens.first_index_of(&token) {
+            Some(index) => index,
+            None => return Err(Error::TokenNotAllowed),
         };
 
-        // Verifies if we're past the start_period already
-        // Allowance only starts to run after the start_period
-        let start_period = read_start_period(&env);
-        if env.ledger().timestamp() < start_period {
-            panic_with_error!(&env, Error::AllowancePeriodNotSarted);
+        // Whatever children hold in it would be out of reach once it's removed
+        if is_token_in_use(&env, token.clone()) {
+            return Err(Error::TokenInUse);
         }
+        tokens.remove_unchecked(index);
+        write_tokens(&env, tokens);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("rmtoken")),
+            (token, env.ledger().timestamp()),
+        );
 
-        // Verifies if we're under the end_period or there isn't an end_period
-        // Allowance only accrues up until the end_period or indefinitely if end_period = 0
-        let end_period = read_end_period(&env);
-        if env.ledger().timestamp() > end_period && end_period > 0 {
-            panic_with_error!(&env, Error::AllowancePeriodEnded);
+        Ok(())
+    }
+
+    fn get_tokens(env: Env) -> Result<Vec<BytesN<32>>, Error> {
+        check_initialized(&env)?;
+        Ok(read_tokens(&env))
+    }
+
+    fn set_allow(env: Env, child_account: AccountId, token: BytesN<32>, allowance: i128) -> Result<(), Error> {
+        let guardian = check_guardian(&env)?;
+        check_token(&env, &token)?;
+
+        // A zero allowance stops accruing, keeping what was already accrued
+        if allowance < 0 {
+            return Err(Error::NegativeAmount);
         }
 
-        if !has_allowance(&env, child_account.clone()){
-            panic_with_error!(&env, Error::ChildNotSet);
+        let action = Action::SetAllow(ChildAllow {
+            child: child_account.clone(),
+            token: token.clone(),
+            amount: allowance,
+        });
+        if !approve(&env, guardian, action) {
+            return Ok(());
         }
 
-        let child_allowance = read_allowance(&env, child_account.clone());
-        let token_address = read_token_address(&env);
-        let step_period = read_step_period(&env);
-        let withdrawn_allowance = read_withdrawn_allowance(&env, child_account.clone());
-        let parent_account = read_admin(&env);
+        // Keeps what was already accrued at the previous allowance, while a new child
+        // only accrues from now on
+        if has_allowance(&env, child_account.clone(), token.clone()) {
+            checkpoint_allowance(&env, child_account.clone(), token.clone());
+        } else {
+            checkpoint_registration(&env, child_account.clone(), token.clone());
+        }
 
-        let token_client = token::Client::new(&env, token_address);
+        // Children with a schedule of their own keep its amount in sync
+        if has_schedule(&env, child_account.clone(), token.clone()) {
+            let mut schedule = read_schedule(&env, child_account.clone(), token.clone());
+            schedule.amount = allowance;
+            write_schedule(&env, child_account.clone(), token.clone(), schedule);
+        }
+        write_allowance(&env, child_account.clone(), token.clone(), allowance);
+        add_child(&env, child_account.clone());
+        start_contract(&env);
 
-        //calculate allowance
-        let amount_available = calculate_allowance_available(
-            &env,
-            start_period,
-            step_period,
-            child_allowance,
-            draw_amount,
+        env.events().publish(
+            (symbol!("allow"), symbol!("set"), child_account),
+            (token, allowance, env.ledger().timestamp()),
         );
 
-        //Verifies if the child is trying to withdraw an amount within the allowance already available
-        if amount_available < 0 {
-            panic_with_error!(&env, Error::InsufficientAllowance);
+        Ok(())
+    }
+
+    fn get_allow(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<i128, Error> {
+        check_child(&env, child_account.clone(), token.clone())?;
+        Ok(read_allowance(&env, child_account, token))
+    }
+
+    fn set_advnc(env: Env, child_account: AccountId, token: BytesN<32>, limit: i128) -> Result<(), Error> {
+        let guardian = check_guardian(&env)?;
+        check_child(&env, child_account.clone(), token.clone())?;
+
+        if limit < 0 {
+            return Err(Error::NegativeAmount);
         }
 
-        //update withdrawn value
-        write_withdrawn_allowance(
-            &env,
-            child_account.clone(),
-            draw_amount + withdrawn_allowance,
+        let action = Action::SetAdvnc(ChildAllow {
+            child: child_account.clone(),
+            token: token.clone(),
+            amount: limit,
+        });
+        if !approve(&env, guardian, action) {
+            return Ok(());
+        }
+        write_advance_limit(&env, child_account.clone(), token.clone(), limit);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("advance"), child_account),
+            (token, limit, env.ledger().timestamp()),
         );
 
-        //Transfer the withdrawn value from the parent account to the child account
-        token_client.xfer_from(
-            &Signature::Invoker,
-            &(0 as i128),
-            &Identifier::Account(parent_account),
-            &Identifier::Account(child_account),
-            &draw_amount,
+        Ok(())
+    }
+
+    fn get_advnc(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<i128, Error> {
+        check_child(&env, child_account.clone(), token.clone())?;
+        Ok(read_advance_limit(&env, child_account, token))
+    }
+
+    fn set_intrst(env: Env, child_account: AccountId, token: BytesN<32>, interest_bps: u32) -> Result<(), Error> {
+        let guardian = check_guardian(&env)?;
+        check_child(&env, child_account.clone(), token.clone())?;
+
+        let action = Action::SetIntrst(ChildRate {
+            child: child_account.clone(),
+            token: token.clone(),
+            rate_bps: interest_bps,
+        });
+        if !approve(&env, guardian, action) {
+            return Ok(());
+        }
+
+        // Keeps the interest already earned at the previous rate. If it grew too big to be
+        // calculated, it's recalculated at the new one instead, so it can still be lowered.
+        if let Some(checkpoint) = calculate_checkpoint(&env, child_account.clone(), token.clone()) {
+            write_checkpoint(&env, child_account.clone(), token.clone(), checkpoint);
+        }
+        write_interest(&env, child_account.clone(), token.clone(), interest_bps);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("interest"), child_account),
+            (token, interest_bps, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn get_intrst(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<u32, Error> {
+        check_child(&env, child_account.clone(), token.clone())?;
+        Ok(read_interest(&env, child_account, token))
+    }
+
+    fn set_stream(env: Env, child_account: AccountId, token: BytesN<32>, streaming: bool) -> Result<(), Error> {
+        check_guardian(&env)?;
+        check_child(&env, child_account.clone(), token.clone())?;
+
+        let mut accrual = read_accrual(&env, child_account.clone(), token.clone());
+        accrual.streaming = streaming;
+        switch_accrual(&env, child_account.clone(), token.clone(), accrual);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("stream"), child_account),
+            (token, streaming, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn get_stream(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<bool, Error> {
+        check_child(&env, child_account.clone(), token.clone())?;
+        Ok(read_streaming(&env, child_account, token))
+    }
+
+    fn set_payout(env: Env, child_account: AccountId, token: BytesN<32>, payout: Payout) -> Result<(), Error> {
+        check_guardian(&env)?;
+        check_child(&env, child_account.clone(), token.clone())?;
+
+        let mut accrual = read_accrual(&env, child_account.clone(), token.clone());
+        accrual.payout = payout.clone();
+        switch_accrual(&env, child_account.clone(), token.clone(), accrual);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("payout"), child_account),
+            (token, payout, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn get_payout(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<Payout, Error> {
+        check_child(&env, child_account.clone(), token.clone())?;
+        Ok(read_payout(&env, child_account, token))
+    }
+
+    fn get_debt(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<i128, Error> {
+        check_child(&env, child_account.clone(), token.clone())?;
+        Ok(read_debt(&env, child_account, token))
+    }
+
+    fn rm_child(env: Env, child_account: AccountId) -> Result<(), Error> {
+        let guardian = check_guardian(&env)?;
+
+        if !is_child(&env, child_account.clone()) {
+            return Err(Error::ChildNotSet);
+        }
+
+        if !approve(&env, guardian, Action::RmChild(child_account.clone())) {
+            return Ok(());
+        }
+        remove_child(&env, child_account.clone());
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("remove"), child_account),
+            env.ledger().timestamp(),
+        );
+
+        Ok(())
+    }
+
+    fn list_child(env: Env) -> Result<Vec<AccountId>, Error> {
+        check_initialized(&env)?;
+        Ok(read_children(&env))
+    }
+
+    fn summary(env: Env) -> Result<Vec<ChildSumm>, Error> {
+        check_initialized(&env)?;
+        Ok(read_summary(&env))
+    }
+
+    fn suspend(env: Env, child_account: AccountId) -> Result<(), Error> {
+        check_guardian(&env)?;
+
+        if !is_child(&env, child_account.clone()) {
+            return Err(Error::ChildNotSet);
+        }
+
+        let mut suspensions = read_suspensions(&env, child_account.clone());
+        if is_ongoing(&suspensions) {
+            return Err(Error::ChildSuspended);
+        }
+        start_pause(&env, &mut suspensions);
+        write_suspensions(&env, child_account.clone(), suspensions);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("suspend"), child_account),
+            env.ledger().timestamp(),
+        );
+
+        Ok(())
+    }
+
+    fn unsuspend(env: Env, child_account: AccountId) -> Result<(), Error> {
+        check_guardian(&env)?;
+
+        let mut suspensions = read_suspensions(&env, child_account.clone());
+        if !is_ongoing(&suspensions) {
+            return Err(Error::InvalidArguments);
+        }
+        end_pause(&env, &mut suspensions);
+        write_suspensions(&env, child_account.clone(), suspensions);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("unsuspend"), child_account),
+            env.ledger().timestamp(),
         );
 
         Ok(())
     }
+
+    fn get_susp(env: Env, child_account: AccountId) -> Result<bool, Error> {
+        check_initialized(&env)?;
+        Ok(is_suspended(&env, child_account))
+    }
+
+    fn pause(env: Env) -> Result<(), Error> {
+        check_guardian(&env)?;
+
+        if read_state(&env) == State::Paused {
+            return Err(Error::ContractPaused);
+        }
+
+        let mut pauses = read_pauses(&env);
+        start_pause(&env, &mut pauses);
+        write_pauses(&env, pauses);
+        write_state(&env, State::Paused);
+
+        env.events()
+            .publish((symbol!("allow"), symbol!("pause")), env.ledger().timestamp());
+
+        Ok(())
+    }
+
+    fn resume(env: Env) -> Result<(), Error> {
+        check_guardian(&env)?;
+
+        if read_state(&env) != State::Paused {
+            return Err(Error::InvalidArguments);
+        }
+
+        let mut pauses = read_pauses(&env);
+        end_pause(&env, &mut pauses);
+        write_pauses(&env, pauses);
+        write_state(&env, State::Initiated);
+        start_contract(&env);
+
+        env.events()
+            .publish((symbol!("allow"), symbol!("resume")), env.ledger().timestamp());
+
+        Ok(())
+    }
+
+    fn get_state(env: Env) -> Result<State, Error> {
+        Ok(read_state(&env))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn set_sched(
+        env: Env,
+        child_account: AccountId,
+        token: BytesN<32>,
+        step_period: u64,
+        start_period: u64,
+        end_period: u64,
+        allowance: i128,
+        calendar: Calendar,
+    ) -> Result<(), Error> {
+        let guardian = check_guardian(&env)?;
+        check_token(&env, &token)?;
+
+        //when start_period is set as 0, the child allowance starts right away
+        let start = if start_period == 0 {
+            env.ledger().timestamp()
+        } else {
+            start_period
+        };
+
+        // Same as the contract step_period, 0 would cause a division by 0
+        check_period(&calendar, step_period, start, end_period)?;
+        if allowance < 0 {
+            return Err(Error::NegativeAmount);
+        }
+
+        let action = Action::SetSched(ChildSched {
+            child: child_account.clone(),
+            token: token.clone(),
+            schedule: Schedule {
+                start: start_period,
+                step: step_period,
+                end: end_period,
+                amount: allowance,
+                calendar: calendar.clone(),
+            },
+        });
+        if !approve(&env, guardian, action) {
+            return Ok(());
+        }
+
+        // Keeps what was already accrued at the previous schedule, while a new child
+        // only accrues from now on
+        if has_allowance(&env, child_account.clone(), token.clone()) {
+            checkpoint_allowance(&env, child_account.clone(), token.clone());
+        } else {
+            checkpoint_registration(&env, child_account.clone(), token.clone());
+        }
+
+        let schedule = Schedule {
+            start,
+            step: step_period,
+            end: end_period,
+            amount: allowance,
+            calendar,
+        };
+        write_schedule(&env, child_account.clone(), token.clone(), schedule.clone());
+        write_allowance(&env, child_account.clone(), token.clone(), allowance);
+        add_child(&env, child_account.clone());
+        start_contract(&env);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("sched"), child_account),
+            (token, schedule, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn get_sched(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<Schedule, Error> {
+        check_child(&env, child_account.clone(), token.clone())?;
+        Ok(read_schedule(&env, child_account, token))
+    }
+
+    fn get_start(env: Env) -> Result<u64, Error> {
+        check_initialized(&env)?;
+        Ok(read_start_period(&env))
+    }
+
+    fn get_step(env: Env) -> Result<u64, Error> {
+        check_initialized(&env)?;
+        Ok(read_step_period(&env))
+    }
+
+    fn get_end(env: Env) -> Result<u64, Error> {
+        check_initialized(&env)?;
+        Ok(read_end_period(&env))
+    }
+
+    fn set_claim(env: Env, claim_window: u64) -> Result<(), Error> {
+        let guardian = check_guardian(&env)?;
+        if !approve(&env, guardian, Action::SetClaim(claim_window)) {
+            return Ok(());
+        }
+        write_claim_window(&env, claim_window);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("claim")),
+            (claim_window, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn get_claim(env: Env) -> Result<u64, Error> {
+        check_initialized(&env)?;
+        Ok(read_claim_window(&env))
+    }
+
+    fn get_aval(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<Available, Error> {
+        check_child(&env, child_account.clone(), token.clone())?;
+        Ok(Available {
+            amount: read_allowance_available(&env, child_account, token.clone()),
+            covered: read_funding(&env, token.clone()) >= read_allowance_outstanding(&env, token),
+        })
+    }
+
+    fn get_wthdr(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<i128, Error> {
+        check_initialized(&env)?;
+        Ok(read_withdrawn_allowance(&env, child_account, token))
+    }
+
+    fn set_limits(
+        env: Env,
+        child_account: AccountId,
+        token: BytesN<32>,
+        per_draw: i128,
+        window: u64,
+        per_window: i128,
+        interval: u64,
+    ) -> Result<(), Error> {
+        check_guardian(&env)?;
+        check_child(&env, child_account.clone(), token.clone())?;
+
+        if per_draw < 0 || per_window < 0 {
+            return Err(Error::NegativeAmount);
+        }
+
+        // A limit per window needs the window it applies to
+        if per_window > 0 && window == 0 {
+            return Err(Error::InvalidPeriod);
+        }
+
+        let limits = Limits {
+            per_draw,
+            window,
+            per_window,
+            interval,
+        };
+        write_limits(&env, child_account.clone(), token.clone(), limits.clone());
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("limits"), child_account),
+            (token, limits, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn get_limits(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<Limits, Error> {
+        check_child(&env, child_account.clone(), token.clone())?;
+        Ok(read_limits(&env, child_account, token))
+    }
+
+    fn set_carry(
+        env: Env,
+        child_account: AccountId,
+        token: BytesN<32>,
+        carryover: Carryover,
+    ) -> Result<(), Error> {
+        let guardian = check_guardian(&env)?;
+        check_child(&env, child_account.clone(), token.clone())?;
+
+        match carryover {
+            Carryover::Amount(amount) if amount < 0 => return Err(Error::NegativeAmount),
+            Carryover::Steps(0) => return Err(Error::InvalidArguments),
+            _ => {}
+        }
+
+        let action = Action::SetCarry(ChildCarry {
+            child: child_account.clone(),
+            token: token.clone(),
+            carryover: carryover.clone(),
+        });
+        if !approve(&env, guardian, action) {
+            return Ok(());
+        }
+
+        // What was forfeited under the previous policy stays forfeited
+        checkpoint_allowance(&env, child_account.clone(), token.clone());
+        write_carryover(&env, child_account.clone(), token.clone(), carryover.clone());
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("carry"), child_account),
+            (token, carryover, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn get_carry(env: Env, child_account: AccountId, token: BytesN<32>) -> Result<Carryover, Error> {
+        check_child(&env, child_account.clone(), token.clone())?;
+        Ok(read_carryover(&env, child_account, token))
+    }
+
+    fn set_escrow(env: Env, enabled: bool) -> Result<(), Error> {
+        check_guardian(&env)?;
+        write_escrow(&env, enabled);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("escrow")),
+            (enabled, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn get_escrow(env: Env) -> Result<bool, Error> {
+        check_initialized(&env)?;
+        Ok(read_escrow(&env))
+    }
+
+    fn deposit(env: Env, token: BytesN<32>, amount: i128) -> Result<(), Error> {
+        let admin = check_admin(&env)?;
+
+        if !read_escrow(&env) {
+            return Err(Error::EscrowDisabled);
+        }
+
+        check_token(&env, &token)?;
+        check_amount(amount)?;
+
+        let token_client = token::Client::new(&env, token.clone());
+        token_client.xfer_from(
+            &Signature::Invoker,
+            &0,
+            &Identifier::Account(admin),
+            &Identifier::Contract(env.get_current_contract()),
+            &amount,
+        );
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("deposit")),
+            (token, amount, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn reclaim(env: Env, token: BytesN<32>, amount: i128) -> Result<(), Error> {
+        let admin = check_admin(&env)?;
+        check_amount(amount)?;
+
+        // The funds still owed to the children stay in escrow
+        let token_client = token::Client::new(&env, token.clone());
+        let mut reclaimable = token_client.balance(&Identifier::Contract(env.get_current_contract()));
+        if read_escrow(&env) {
+            reclaimable -= read_allowance_outstanding(&env, token.clone());
+        }
+        if amount > reclaimable {
+            return Err(Error::EscrowInsufficient);
+        }
+
+        token_client.xfer(&Signature::Invoker, &0, &Identifier::Account(admin), &amount);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("reclaim")),
+            (token, amount, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn fund_stat(env: Env, token: BytesN<32>) -> Result<Funding, Error> {
+        check_initialized(&env)?;
+        Ok(read_funding_status(&env, token))
+    }
+
+    fn get_nonce(env: Env, child_account: AccountId) -> Result<i128, Error> {
+        check_initialized(&env)?;
+        Ok(read_nonce(&env, child_account))
+    }
+
+    fn withdraw(
+        env: Env,
+        child_sig: Signature,
+        nonce: i128,
+        child_account: AccountId,
+        token: BytesN<32>,
+        draw_amount: i128,
+    ) -> Result<(), Error> {
+        check_initialized(&env)?;
+
+        // Only the child itself can pull its allowance. The signature must
+        // belong to a user account and match the child being paid.
+        check_child_sig(&env, &child_sig, nonce, child_account.clone())?;
+        check_amount(draw_amount)?;
+        verify(
+            &env,
+            &child_sig,
+            symbol!("withdraw"),
+            (&child_account, &token, nonce, draw_amount),
+        );
+
+        match read_state(&env) {
+            State::Paused => return Err(Error::ContractPaused),
+            // No allowance was ever set up, so there's nothing to withdraw yet
+            State::Initiated => return Err(Error::NotStarted),
+            _ => {}
+        }
+
+        check_token(&env, &token)?;
+
+        if !has_allowance(&env, child_account.clone(), token.clone()){
+            return Err(Error::ChildNotSet);
+        }
+
+        if is_suspended(&env, child_account.clone()) {
+            return Err(Error::ChildSuspended);
+        }
+
+        // Verifies if we're past the child's start_period already
+        // Allowance only starts to run after the start_period
+        let schedule = read_schedule(&env, child_account.clone(), token.clone());
+        if env.ledger().timestamp() < schedule.start {
+            return Err(Error::AllowancePeriodNotSarted);
+        }
+
+        // Allowance only accrues up until the end_period or indefinitely if end_period = 0.
+        // What was accrued can still be claimed until the claim window after it is over.
+        if is_claim_window_closed(&env, schedule.end, read_claim_window(&env)) {
+            return Err(Error::AllowancePeriodEnded);
+        }
+
+        // Forfeits what is over the carryover policy before paying out, so the
+        // checkpoint holds everything accrued up to now
+        checkpoint_allowance(&env, child_account.clone(), token.clone());
+
+        //Verifies if the child is trying to withdraw an amount within the allowance already available,
+        //or within its advance limit beyond it
+        let withdrawn_allowance =
+            check_withdrawable(&env, child_account.clone(), token.clone(), draw_amount)?;
+
+        check_limits(&env, child_account.clone(), token.clone(), draw_amount)?;
+        check_funding(&env, token.clone(), draw_amount)?;
+
+        //update withdrawn value
+        write_withdrawn_allowance(
+            &env,
+            child_account.clone(),
+            token.clone(),
+            withdrawn_allowance,
+        );
+        spend_credits(&env, child_account.clone(), token.clone());
+        record_draw(&env, child_account.clone(), token.clone(), draw_amount);
+
+        //Transfer the withdrawn value to the child account
+        pay_child(&env, child_account.clone(), token.clone(), draw_amount);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("withdraw"), child_account),
+            (token, draw_amount, withdrawn_allowance, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn request(
+        env: Env,
+        child_sig: Signature,
+        nonce: i128,
+        child_account: AccountId,
+        token: BytesN<32>,
+        amount: i128,
+        memo: Bytes,
+    ) -> Result<u32, Error> {
+        check_initialized(&env)?;
+
+        check_child_sig(&env, &child_sig, nonce, child_account.clone())?;
+        verify(
+            &env,
+            &child_sig,
+            symbol!("request"),
+            (&child_account, &token, nonce, amount, &memo),
+        );
+
+        check_token(&env, &token)?;
+        check_child(&env, child_account.clone(), token.clone())?;
+
+        check_amount(amount)?;
+        if memo.len() > MAX_MEMO_LEN {
+            return Err(Error::InvalidArguments);
+        }
+
+        let id = add_request(
+            &env,
+            Request {
+                child: child_account.clone(),
+                token: token.clone(),
+                amount,
+                memo: memo.clone(),
+                status: ReqStatus::Pending,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("request"), child_account),
+            (id, token, amount, memo, env.ledger().timestamp()),
+        );
+
+        Ok(id)
+    }
+
+    fn aprv_req(env: Env, id: u32, advance: bool) -> Result<(), Error> {
+        check_admin(&env)?;
+        let mut request = check_request(&env, id)?;
+        let child_account = request.child.clone();
+        let token = request.token.clone();
+
+        check_token(&env, &token)?;
+        check_child(&env, child_account.clone(), token.clone())?;
+        check_not_frozen(&env, child_account.clone())?;
+
+        // Forfeits what is over the carryover policy before paying out
+        checkpoint_allowance(&env, child_account.clone(), token.clone());
+        if !advance
+            && request.amount > read_allowance_available(&env, child_account.clone(), token.clone())
+        {
+            return Err(Error::InsufficientAllowance);
+        }
+
+        check_funding(&env, token.clone(), request.amount)?;
+
+        // Advances take the withdrawn total above the accrued one,
+        // so the allowance accrued next goes towards paying them back
+        let withdrawn_allowance = read_withdrawn_allowance(&env, child_account.clone(), token.clone())
+            .checked_add(request.amount)
+            .ok_or(Error::Overflow)?;
+        write_withdrawn_allowance(&env, child_account.clone(), token.clone(), withdrawn_allowance);
+        spend_credits(&env, child_account.clone(), token.clone());
+
+        request.status = if advance {
+            ReqStatus::Advanced
+        } else {
+            ReqStatus::Approved
+        };
+        write_request(&env, id, request.clone());
+
+        pay_child(&env, child_account.clone(), token.clone(), request.amount);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("reqapprove"), child_account),
+            (id, token, request.amount, advance, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn rjct_req(env: Env, id: u32) -> Result<(), Error> {
+        check_admin(&env)?;
+        let mut request = check_request(&env, id)?;
+
+        request.status = ReqStatus::Rejected;
+        write_request(&env, id, request.clone());
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("reqreject"), request.child),
+            (id, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn get_req(env: Env, id: u32) -> Result<Request, Error> {
+        check_initialized(&env)?;
+        if !has_request(&env, id) {
+            return Err(Error::RequestNotFound);
+        }
+        Ok(read_request(&env, id))
+    }
+
+    fn add_chore(
+        env: Env,
+        child_account: AccountId,
+        token: BytesN<32>,
+        reward: i128,
+        memo: Bytes,
+    ) -> Result<u32, Error> {
+        check_guardian(&env)?;
+        check_token(&env, &token)?;
+        check_child(&env, child_account.clone(), token.clone())?;
+
+        check_amount(reward)?;
+        if memo.len() > MAX_MEMO_LEN {
+            return Err(Error::InvalidArguments);
+        }
+
+        let id = add_chore(&env, child_account.clone(), token.clone(), reward, memo.clone());
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("chore"), child_account),
+            (id, token, reward, memo, env.ledger().timestamp()),
+        );
+
+        Ok(id)
+    }
+
+    fn done_chore(env: Env, id: u32) -> Result<(), Error> {
+        let guardian = check_guardian(&env)?;
+        if !has_chore(&env, id) {
+            return Err(Error::ChoreNotFound);
+        }
+
+        let mut chore = read_chore(&env, id);
+        if chore.done {
+            return Err(Error::ChoreDone);
+        }
+        check_child(&env, chore.child.clone(), chore.token.clone())?;
+        if !approve(&env, guardian, Action::DoneChore(id)) {
+            return Ok(());
+        }
+
+        chore.done = true;
+        write_chore(&env, chore.clone());
+        credit_allowance(&env, chore.child.clone(), chore.token.clone(), chore.reward);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("choredone"), chore.child),
+            (id, chore.token, chore.reward, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn get_chore(env: Env, id: u32) -> Result<Chore, Error> {
+        check_initialized(&env)?;
+        if !has_chore(&env, id) {
+            return Err(Error::ChoreNotFound);
+        }
+        Ok(read_chore(&env, id))
+    }
+
+    fn get_chores(env: Env, child_account: AccountId) -> Result<Vec<Chore>, Error> {
+        check_initialized(&env)?;
+        Ok(read_chores(&env, child_account))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_goal(
+        env: Env,
+        child_sig: Signature,
+        nonce: i128,
+        child_account: AccountId,
+        token: BytesN<32>,
+        memo: Bytes,
+        target: i128,
+        unlock: u64,
+    ) -> Result<u32, Error> {
+        check_initialized(&env)?;
+
+        check_child_sig(&env, &child_sig, nonce, child_account.clone())?;
+        verify(
+            &env,
+            &child_sig,
+            symbol!("add_goal"),
+            (&child_account, &token, nonce, &memo, target, unlock),
+        );
+
+        check_token(&env, &token)?;
+        check_child(&env, child_account.clone(), token.clone())?;
+
+        check_amount(target)?;
+        if memo.len() > MAX_MEMO_LEN {
+            return Err(Error::InvalidArguments);
+        }
+
+        let id = add_goal(&env, child_account.clone(), token.clone(), memo.clone(), target, unlock);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("goal"), child_account),
+            (id, token, memo, target, unlock, env.ledger().timestamp()),
+        );
+
+        Ok(id)
+    }
+
+    fn save_goal(
+        env: Env,
+        child_sig: Signature,
+        nonce: i128,
+        id: u32,
+        amount: i128,
+    ) -> Result<(), Error> {
+        check_initialized(&env)?;
+        let mut goal = check_goal(&env, id)?;
+        let child_account = goal.child.clone();
+        let token = goal.token.clone();
+
+        check_child_sig(&env, &child_sig, nonce, child_account.clone())?;
+        verify(&env, &child_sig, symbol!("save_goal"), (id, nonce, amount));
+
+        check_child(&env, child_account.clone(), token.clone())?;
+        check_not_frozen(&env, child_account.clone())?;
+
+        check_amount(amount)?;
+
+        // Forfeits what is over the carryover policy before locking it
+        checkpoint_allowance(&env, child_account.clone(), token.clone());
+        if amount > read_allowance_available(&env, child_account.clone(), token.clone()) {
+            return Err(Error::InsufficientAllowance);
+        }
+
+        // Savings leave the allowance available as if they were withdrawn
+        let withdrawn_allowance = read_withdrawn_allowance(&env, child_account.clone(), token.clone())
+            .checked_add(amount)
+            .ok_or(Error::Overflow)?;
+        write_withdrawn_allowance(&env, child_account.clone(), token.clone(), withdrawn_allowance);
+        spend_credits(&env, child_account.clone(), token);
+
+        goal.saved = goal.saved.checked_add(amount).ok_or(Error::Overflow)?;
+        write_goal(&env, goal.clone());
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("save"), child_account),
+            (id, amount, goal.saved, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn match_goal(env: Env, id: u32, match_bps: u32) -> Result<(), Error> {
+        let guardian = check_guardian(&env)?;
+        let mut goal = check_goal(&env, id)?;
+        if match_bps > MAX_MATCH_BPS {
+            return Err(Error::InvalidArguments);
+        }
+
+        let action = Action::MatchGoal(GoalMatch { id, match_bps });
+        if !approve(&env, guardian, action) {
+            return Ok(());
+        }
+
+        goal.match_bps = match_bps;
+        write_goal(&env, goal.clone());
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("goalmatch"), goal.child),
+            (id, match_bps, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn wthdr_goal(env: Env, child_sig: Signature, nonce: i128, id: u32) -> Result<(), Error> {
+        check_initialized(&env)?;
+        let mut goal = check_goal(&env, id)?;
+        let child_account = goal.child.clone();
+        let token = goal.token.clone();
+
+        check_child_sig(&env, &child_sig, nonce, child_account.clone())?;
+        verify(&env, &child_sig, symbol!("wthdr_goal"), (id, nonce));
+
+        check_token(&env, &token)?;
+        check_not_frozen(&env, child_account.clone())?;
+
+        if !is_goal_unlocked(&env, &goal) {
+            return Err(Error::GoalLocked);
+        }
+
+        // What was saved comes out of the allowance, so it follows the spending
+        // limits like a withdraw, while the parent's match is exempt from them
+        check_limits(&env, child_account.clone(), token.clone(), goal.saved)?;
+
+        let amount = calculate_goal_balance(&goal).ok_or(Error::Overflow)?;
+        check_funding(&env, token.clone(), amount)?;
+
+        record_draw(&env, child_account.clone(), token.clone(), goal.saved);
+        goal.released = true;
+        write_goal(&env, goal);
+
+        pay_child(&env, child_account.clone(), token.clone(), amount);
+
+        env.events().publish(
+            (symbol!("allow"), symbol!("goaldone"), child_account),
+            (id, token, amount, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    fn get_goal(env: Env, id: u32) -> Result<Goal, Error> {
+        check_initialized(&env)?;
+        if !has_goal(&env, id) {
+            return Err(Error::GoalNotFound);
+        }
+        Ok(read_goal(&env, id))
+    }
+
+    fn get_goals(env: Env, child_account: AccountId) -> Result<Vec<Goal>, Error> {
+        check_initialized(&env)?;
+        Ok(read_goals(&env, child_account))
+    }
 }
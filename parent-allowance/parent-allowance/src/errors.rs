@@ -14,4 +14,28 @@ pub enum Error {
     AllowancePeriodEnded = 8,
     AllowancePeriodNotSarted = 9,
     ChildNotSet = 10,
+    InvalidNonce = 11,
+    ContractPaused = 12,
+    ChildSuspended = 13,
+    NoAdminProposal = 14,
+    AdminProposalExpired = 15,
+    TokenNotAllowed = 16,
+    EscrowDisabled = 17,
+    EscrowInsufficient = 18,
+    ParentApprovalInsufficient = 19,
+    ParentBalanceInsufficient = 20,
+    DrawLimitExceeded = 21,
+    WindowLimitExceeded = 22,
+    DrawTooSoon = 23,
+    RequestNotFound = 24,
+    RequestNotPending = 25,
+    ChoreNotFound = 26,
+    ChoreDone = 27,
+    GoalNotFound = 28,
+    GoalLocked = 29,
+    GoalReleased = 30,
+    Overflow = 31,
+    NegativeAmount = 32,
+    InvalidPeriod = 33,
+    TokenInUse = 34,
 }
\ No newline at end of file
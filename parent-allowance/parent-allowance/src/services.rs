@@ -1,7 +1,19 @@
 
-use crate::context::{DataKey, State};
+use crate::context::{
+    Action, AdminProp, Calendar, Carryover, Checkpoint, Chore, Goal, ChildSumm, ChildToken, DataKey, Draw, Funding, Guardians, Limits,
+    Pause, Payout, Request, Schedule, State,
+};
+use crate::contract::token;
 use crate::errors::Error;
-use soroban_sdk::{AccountId, Address, BytesN, Env};
+use soroban_auth::{Identifier, Signature};
+use soroban_sdk::{panic_with_error, AccountId, Address, Bytes, BytesN, Env, Vec};
+
+fn child_token(child_account: AccountId, token: BytesN<32>) -> ChildToken {
+    ChildToken {
+        child: child_account,
+        token,
+    }
+}
 
 //
 // Write functions
@@ -15,12 +27,226 @@ pub fn write_admin(env: &Env, admin: AccountId) {
     env.storage().set(DataKey::Admin, admin);
 }
 
-pub fn write_token_address(env: &Env, token_address: BytesN<32>) {
-    env.storage().set(DataKey::TokenAddr, token_address);
+pub fn write_admin_proposal(env: &Env, proposal: AdminProp) {
+    env.storage().set(DataKey::PendAdmin, proposal);
+}
+
+pub fn remove_admin_proposal(env: &Env) {
+    env.storage().remove(DataKey::PendAdmin);
+}
+
+pub fn write_guardians(env: &Env, guardians: Guardians) {
+    env.storage().set(DataKey::Guardians, guardians);
+}
+
+pub fn write_approvals(env: &Env, action: Action, approvals: Vec<AccountId>) {
+    env.storage().set(DataKey::Approvals(action), approvals);
+}
+
+pub fn write_tokens(env: &Env, tokens: Vec<BytesN<32>>) {
+    env.storage().set(DataKey::Tokens, tokens);
+}
+
+pub fn write_children(env: &Env, children: Vec<AccountId>) {
+    env.storage().set(DataKey::Children, children);
+}
+
+// Adds a child account to the index of registered children, if it isn't there yet
+pub fn add_child(env: &Env, child_account: AccountId) {
+    let mut children = read_children(env);
+    if !children.contains(&child_account) {
+        children.push_back(child_account);
+        write_children(env, children);
+    }
+}
+
+pub fn write_escrow(env: &Env, enabled: bool) {
+    env.storage().set(DataKey::Escrow, enabled);
+}
+
+pub fn write_allowance(env: &Env, child_account: AccountId, token: BytesN<32>, allowance: i128) {
+    env.storage()
+        .set(DataKey::Allowance(child_token(child_account, token)), allowance);
+}
+
+pub fn write_schedule(env: &Env, child_account: AccountId, token: BytesN<32>, schedule: Schedule) {
+    env.storage()
+        .set(DataKey::Schedule(child_token(child_account, token)), schedule);
+}
+
+pub fn write_checkpoint(
+    env: &Env,
+    child_account: AccountId,
+    token: BytesN<32>,
+    checkpoint: Checkpoint,
+) {
+    env.storage()
+        .set(DataKey::Checkpnt(child_token(child_account, token)), checkpoint);
+}
+
+pub fn write_pauses(env: &Env, pauses: Vec<Pause>) {
+    env.storage().set(DataKey::Pauses, pauses);
+}
+
+pub fn write_suspensions(env: &Env, child_account: AccountId, suspensions: Vec<Pause>) {
+    env.storage().set(DataKey::Suspends(child_account), suspensions);
+}
+
+pub fn write_limits(env: &Env, child_account: AccountId, token: BytesN<32>, limits: Limits) {
+    env.storage()
+        .set(DataKey::Limits(child_token(child_account, token)), limits);
+}
+
+pub fn write_draws(env: &Env, child_account: AccountId, token: BytesN<32>, draws: Vec<Draw>) {
+    env.storage()
+        .set(DataKey::Draws(child_token(child_account, token)), draws);
+}
+
+pub fn write_carryover(env: &Env, child_account: AccountId, token: BytesN<32>, carryover: Carryover) {
+    env.storage()
+        .set(DataKey::Carryover(child_token(child_account, token)), carryover);
+}
+
+pub fn write_credits(env: &Env, child_account: AccountId, token: BytesN<32>, credits: i128) {
+    env.storage()
+        .set(DataKey::Credits(child_token(child_account, token)), credits);
+}
+
+pub fn write_request(env: &Env, id: u32, request: Request) {
+    env.storage().set(DataKey::Request(id), request);
+}
+
+// Stores a new withdraw request, returning its id
+pub fn add_request(env: &Env, request: Request) -> u32 {
+    let id: u32 = env.storage().get(DataKey::ReqCount).unwrap_or(Ok(0)).unwrap();
+    write_request(env, id, request);
+    env.storage().set(DataKey::ReqCount, id + 1);
+    id
 }
 
-pub fn write_allowance(env: &Env, child_account: AccountId, allowance: i128) {
-    env.storage().set(DataKey::Allowance(child_account), allowance);
+pub fn write_chore(env: &Env, chore: Chore) {
+    env.storage().set(DataKey::Chore(chore.id), chore);
+}
+
+// Stores a new chore, indexing it under its child, and returns its id
+pub fn add_chore(
+    env: &Env,
+    child_account: AccountId,
+    token: BytesN<32>,
+    reward: i128,
+    memo: Bytes,
+) -> u32 {
+    let id: u32 = env.storage().get(DataKey::ChoreCount).unwrap_or(Ok(0)).unwrap();
+    write_chore(
+        env,
+        Chore {
+            id,
+            child: child_account.clone(),
+            token,
+            reward,
+            memo,
+            done: false,
+        },
+    );
+    env.storage().set(DataKey::ChoreCount, id + 1);
+
+    let mut chores = read_chore_ids(env, child_account.clone());
+    chores.push_back(id);
+    env.storage().set(DataKey::Chores(child_account), chores);
+    id
+}
+
+pub fn write_goal(env: &Env, goal: Goal) {
+    env.storage().set(DataKey::Goal(goal.id), goal);
+}
+
+// Stores a new savings goal, indexing it under its child, and returns its id
+pub fn add_goal(
+    env: &Env,
+    child_account: AccountId,
+    token: BytesN<32>,
+    memo: Bytes,
+    target: i128,
+    unlock: u64,
+) -> u32 {
+    let id: u32 = env.storage().get(DataKey::GoalCount).unwrap_or(Ok(0)).unwrap();
+    write_goal(
+        env,
+        Goal {
+            id,
+            child: child_account.clone(),
+            token,
+            memo,
+            target,
+            unlock,
+            saved: 0,
+            match_bps: 0,
+            released: false,
+        },
+    );
+    env.storage().set(DataKey::GoalCount, id + 1);
+
+    let mut goals = read_goal_ids(env, child_account.clone());
+    goals.push_back(id);
+    env.storage().set(DataKey::Goals(child_account), goals);
+    id
+}
+
+pub fn write_interest(env: &Env, child_account: AccountId, token: BytesN<32>, interest_bps: u32) {
+    env.storage()
+        .set(DataKey::Interest(child_token(child_account, token)), interest_bps);
+}
+
+pub fn write_streaming(env: &Env, child_account: AccountId, token: BytesN<32>, streaming: bool) {
+    env.storage()
+        .set(DataKey::Stream(child_token(child_account, token)), streaming);
+}
+
+pub fn write_payout(env: &Env, child_account: AccountId, token: BytesN<32>, payout: Payout) {
+    env.storage()
+        .set(DataKey::Payout(child_token(child_account, token)), payout);
+}
+
+pub fn write_advance_limit(env: &Env, child_account: AccountId, token: BytesN<32>, limit: i128) {
+    env.storage()
+        .set(DataKey::AdvLimit(child_token(child_account, token)), limit);
+}
+
+// Removes everything stored for a given child account, except its nonce,
+// so old signatures can't be replayed if the child is ever registered again.
+pub fn remove_child(env: &Env, child_account: AccountId) {
+    for token in read_tokens(env).iter_unchecked() {
+        let key = child_token(child_account.clone(), token);
+        env.storage().remove(DataKey::Allowance(key.clone()));
+        env.storage().remove(DataKey::WithdAllow(key.clone()));
+        env.storage().remove(DataKey::Schedule(key.clone()));
+        env.storage().remove(DataKey::Checkpnt(key.clone()));
+        env.storage().remove(DataKey::Limits(key.clone()));
+        env.storage().remove(DataKey::Draws(key.clone()));
+        env.storage().remove(DataKey::Carryover(key.clone()));
+        env.storage().remove(DataKey::AdvLimit(key.clone()));
+        env.storage().remove(DataKey::Interest(key.clone()));
+        env.storage().remove(DataKey::Stream(key.clone()));
+        env.storage().remove(DataKey::Payout(key.clone()));
+        env.storage().remove(DataKey::Credits(key));
+    }
+    env.storage().remove(DataKey::Suspends(child_account.clone()));
+
+    for id in read_chore_ids(env, child_account.clone()).iter_unchecked() {
+        env.storage().remove(DataKey::Chore(id));
+    }
+    env.storage().remove(DataKey::Chores(child_account.clone()));
+
+    for id in read_goal_ids(env, child_account.clone()).iter_unchecked() {
+        env.storage().remove(DataKey::Goal(id));
+    }
+    env.storage().remove(DataKey::Goals(child_account.clone()));
+
+    let mut children = read_children(env);
+    if let Some(index) = children.first_index_of(&child_account) {
+        children.remove_unchecked(index);
+        write_children(env, children);
+    }
 }
 
 pub fn write_start_period(env: &Env, start_period: u64) {
@@ -31,12 +257,26 @@ pub fn write_end_period(env: &Env, end_period: u64) {
     env.storage().set(DataKey::EndPeriod, end_period);
 }
 
+pub fn write_claim_window(env: &Env, claim_window: u64) {
+    env.storage().set(DataKey::ClaimWndw, claim_window);
+}
+
 pub fn write_step_period(env: &Env, step_period: u64) {
     env.storage().set(DataKey::StpPeriod, step_period);
 }
 
-pub fn write_withdrawn_allowance(env: &Env, child_account: AccountId, amount: i128) {
-    env.storage().set(DataKey::WithdAllow(child_account), amount);
+pub fn write_withdrawn_allowance(
+    env: &Env,
+    child_account: AccountId,
+    token: BytesN<32>,
+    amount: i128,
+) {
+    env.storage()
+        .set(DataKey::WithdAllow(child_token(child_account, token)), amount);
+}
+
+pub fn write_nonce(env: &Env, child_account: AccountId, nonce: i128) {
+    env.storage().set(DataKey::Nonce(child_account), nonce);
 }
 
 
@@ -56,12 +296,279 @@ pub fn read_admin(env: &Env) -> AccountId {
     env.storage().get_unchecked(DataKey::Admin).unwrap()
 }
 
-pub fn read_allowance(env: &Env, child_account: AccountId) -> i128 {
-    env.storage().get_unchecked(DataKey::Allowance(child_account)).unwrap()
+pub fn has_admin_proposal(env: &Env) -> bool {
+    env.storage().has(DataKey::PendAdmin)
+}
+
+pub fn read_admin_proposal(env: &Env) -> AdminProp {
+    env.storage().get_unchecked(DataKey::PendAdmin).unwrap()
+}
+
+pub fn read_guardians(env: &Env) -> Guardians {
+    env.storage().get_unchecked(DataKey::Guardians).unwrap()
+}
+
+pub fn read_approvals(env: &Env, action: Action) -> Vec<AccountId> {
+    env.storage()
+        .get(DataKey::Approvals(action))
+        .unwrap_or(Ok(Vec::new(env)))
+        .unwrap()
+}
+
+pub fn read_tokens(env: &Env) -> Vec<BytesN<32>> {
+    env.storage().get_unchecked(DataKey::Tokens).unwrap()
+}
+
+pub fn is_token_allowed(env: &Env, token: &BytesN<32>) -> bool {
+    read_tokens(env).contains(token)
+}
+
+pub fn read_children(env: &Env) -> Vec<AccountId> {
+    env.storage()
+        .get(DataKey::Children)
+        .unwrap_or(Ok(Vec::new(env)))
+        .unwrap()
+}
+
+pub fn read_escrow(env: &Env) -> bool {
+    env.storage().get(DataKey::Escrow).unwrap_or(Ok(false)).unwrap()
+}
+
+pub fn read_allowance(env: &Env, child_account: AccountId, token: BytesN<32>) -> i128 {
+    env.storage()
+        .get_unchecked(DataKey::Allowance(child_token(child_account, token)))
+        .unwrap()
+}
+
+pub fn has_allowance(env: &Env, child_account: AccountId, token: BytesN<32>) -> bool {
+    env.storage()
+        .has(DataKey::Allowance(child_token(child_account, token)))
+}
+
+// Checks if a child account has an allowance set in any of the allowed tokens
+pub fn is_child(env: &Env, child_account: AccountId) -> bool {
+    for token in read_tokens(env).iter_unchecked() {
+        if has_allowance(env, child_account.clone(), token) {
+            return true;
+        }
+    }
+    false
+}
+
+// Checks if any child still has an allowance, or savings not released yet, in a token
+pub fn is_token_in_use(env: &Env, token: BytesN<32>) -> bool {
+    for child_account in read_children(env).iter_unchecked() {
+        if has_allowance(env, child_account.clone(), token.clone()) {
+            return true;
+        }
+        for goal in read_goals(env, child_account).iter_unchecked() {
+            if goal.token == token && !goal.released {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+pub fn has_schedule(env: &Env, child_account: AccountId, token: BytesN<32>) -> bool {
+    env.storage()
+        .has(DataKey::Schedule(child_token(child_account, token)))
+}
+
+// Reads the schedule of a given child account in a token. Children without a
+// schedule of their own follow the contract defaults written by `initialize`.
+pub fn read_schedule(env: &Env, child_account: AccountId, token: BytesN<32>) -> Schedule {
+    if has_schedule(env, child_account.clone(), token.clone()) {
+        return env
+            .storage()
+            .get_unchecked(DataKey::Schedule(child_token(child_account, token)))
+            .unwrap();
+    }
+
+    Schedule {
+        start: read_start_period(env),
+        step: read_step_period(env),
+        end: read_end_period(env),
+        amount: read_allowance(env, child_account, token),
+        calendar: Calendar::Seconds,
+    }
+}
+
+// Reads the last checkpoint of a given child account in a token. Without one,
+// the allowance is accrued from the beginning of the child's schedule.
+pub fn read_checkpoint(env: &Env, child_account: AccountId, token: BytesN<32>) -> Checkpoint {
+    env.storage()
+        .get(DataKey::Checkpnt(child_token(child_account, token)))
+        .unwrap_or(Ok(Checkpoint {
+            accrued: 0,
+            timestamp: 0,
+            fraction: 0,
+        }))
+        .unwrap()
+}
+
+// Reads the spending limits of a given child account in a token. Without
+// limits of its own, the child can withdraw whatever is available.
+pub fn read_limits(env: &Env, child_account: AccountId, token: BytesN<32>) -> Limits {
+    env.storage()
+        .get(DataKey::Limits(child_token(child_account, token)))
+        .unwrap_or(Ok(Limits {
+            per_draw: 0,
+            window: 0,
+            per_window: 0,
+            interval: 0,
+        }))
+        .unwrap()
+}
+
+pub fn read_draws(env: &Env, child_account: AccountId, token: BytesN<32>) -> Vec<Draw> {
+    env.storage()
+        .get(DataKey::Draws(child_token(child_account, token)))
+        .unwrap_or(Ok(Vec::new(env)))
+        .unwrap()
+}
+
+// Reads the carryover policy of a given child account in a token. Without
+// one of its own, the whole unclaimed allowance carries over.
+pub fn read_carryover(env: &Env, child_account: AccountId, token: BytesN<32>) -> Carryover {
+    env.storage()
+        .get(DataKey::Carryover(child_token(child_account, token)))
+        .unwrap_or(Ok(Carryover::Unlimited))
+        .unwrap()
+}
+
+// Reads how much of what was credited to a given child account in a token on
+// top of its allowance, e.g. chore rewards, wasn't withdrawn yet
+pub fn read_credits(env: &Env, child_account: AccountId, token: BytesN<32>) -> i128 {
+    env.storage()
+        .get(DataKey::Credits(child_token(child_account, token)))
+        .unwrap_or(Ok(0))
+        .unwrap()
+}
+
+pub fn has_request(env: &Env, id: u32) -> bool {
+    env.storage().has(DataKey::Request(id))
+}
+
+pub fn read_request(env: &Env, id: u32) -> Request {
+    env.storage().get_unchecked(DataKey::Request(id)).unwrap()
+}
+
+// Reads how far below zero the allowance of a given child account in a token
+// can go. Without a limit of its own, the child can't withdraw in advance.
+pub fn read_advance_limit(env: &Env, child_account: AccountId, token: BytesN<32>) -> i128 {
+    env.storage()
+        .get(DataKey::AdvLimit(child_token(child_account, token)))
+        .unwrap_or(Ok(0))
+        .unwrap()
+}
+
+pub fn has_chore(env: &Env, id: u32) -> bool {
+    env.storage().has(DataKey::Chore(id))
+}
+
+pub fn read_chore(env: &Env, id: u32) -> Chore {
+    env.storage().get_unchecked(DataKey::Chore(id)).unwrap()
+}
+
+pub fn read_chore_ids(env: &Env, child_account: AccountId) -> Vec<u32> {
+    env.storage()
+        .get(DataKey::Chores(child_account))
+        .unwrap_or(Ok(Vec::new(env)))
+        .unwrap()
+}
+
+// Reads every chore assigned to a given child account
+pub fn read_chores(env: &Env, child_account: AccountId) -> Vec<Chore> {
+    let mut chores = Vec::new(env);
+    for id in read_chore_ids(env, child_account).iter_unchecked() {
+        chores.push_back(read_chore(env, id));
+    }
+    chores
+}
+
+pub fn has_goal(env: &Env, id: u32) -> bool {
+    env.storage().has(DataKey::Goal(id))
+}
+
+pub fn read_goal(env: &Env, id: u32) -> Goal {
+    env.storage().get_unchecked(DataKey::Goal(id)).unwrap()
+}
+
+pub fn read_goal_ids(env: &Env, child_account: AccountId) -> Vec<u32> {
+    env.storage()
+        .get(DataKey::Goals(child_account))
+        .unwrap_or(Ok(Vec::new(env)))
+        .unwrap()
+}
+
+// Reads every savings goal of a given child account
+pub fn read_goals(env: &Env, child_account: AccountId) -> Vec<Goal> {
+    let mut goals = Vec::new(env);
+    for id in read_goal_ids(env, child_account).iter_unchecked() {
+        goals.push_back(read_goal(env, id));
+    }
+    goals
+}
+
+// Reads the interest, in basis points per step, the unspent allowance of a given
+// child account in a token earns. Without one of its own, it earns none.
+pub fn read_interest(env: &Env, child_account: AccountId, token: BytesN<32>) -> u32 {
+    env.storage()
+        .get(DataKey::Interest(child_token(child_account, token)))
+        .unwrap_or(Ok(0))
+        .unwrap()
+}
+
+// Reads if the allowance of a given child account in a token streams per second.
+// Without a mode of its own, it's paid in full steps.
+pub fn read_streaming(env: &Env, child_account: AccountId, token: BytesN<32>) -> bool {
+    env.storage()
+        .get(DataKey::Stream(child_token(child_account, token)))
+        .unwrap_or(Ok(false))
+        .unwrap()
+}
+
+// Reads when each step of a given child account in a token is paid. Without
+// a timing of its own, steps are paid at the end of their period.
+pub fn read_payout(env: &Env, child_account: AccountId, token: BytesN<32>) -> Payout {
+    env.storage()
+        .get(DataKey::Payout(child_token(child_account, token)))
+        .unwrap_or(Ok(Payout::End))
+        .unwrap()
+}
+
+// How the allowance of a child in a token accrues on top of its schedule
+pub struct Accrual {
+    pub interest_bps: u32,
+    pub streaming: bool,
+    pub payout: Payout,
+}
+
+pub fn read_accrual(env: &Env, child_account: AccountId, token: BytesN<32>) -> Accrual {
+    Accrual {
+        interest_bps: read_interest(env, child_account.clone(), token.clone()),
+        streaming: read_streaming(env, child_account.clone(), token.clone()),
+        payout: read_payout(env, child_account, token),
+    }
+}
+
+pub fn read_pauses(env: &Env) -> Vec<Pause> {
+    env.storage()
+        .get(DataKey::Pauses)
+        .unwrap_or(Ok(Vec::new(env)))
+        .unwrap()
+}
+
+pub fn read_suspensions(env: &Env, child_account: AccountId) -> Vec<Pause> {
+    env.storage()
+        .get(DataKey::Suspends(child_account))
+        .unwrap_or(Ok(Vec::new(env)))
+        .unwrap()
 }
 
-pub fn has_allowance(env: &Env, child_account: AccountId) -> bool {
-    env.storage().has(DataKey::Allowance(child_account))
+pub fn is_suspended(env: &Env, child_account: AccountId) -> bool {
+    is_ongoing(&read_suspensions(env, child_account))
 }
 
 pub fn read_start_period(env: &Env) -> u64 {
@@ -72,16 +579,23 @@ pub fn read_end_period(env: &Env) -> u64 {
     env.storage().get_unchecked(DataKey::EndPeriod).unwrap()
 }
 
+pub fn read_claim_window(env: &Env) -> u64 {
+    env.storage().get(DataKey::ClaimWndw).unwrap_or(Ok(0)).unwrap()
+}
+
 pub fn read_step_period(env: &Env) -> u64 {
     env.storage().get_unchecked(DataKey::StpPeriod).unwrap()
 }
 
-pub fn read_token_address(env: &Env) -> BytesN<32> {
-    env.storage().get_unchecked(DataKey::TokenAddr).unwrap()
+pub fn read_withdrawn_allowance(env: &Env, child_account: AccountId, token: BytesN<32>) -> i128 {
+    env.storage()
+        .get(DataKey::WithdAllow(child_token(child_account, token)))
+        .unwrap_or(Ok(0))
+        .unwrap()
 }
 
-pub fn read_withdrawn_allowance(env: &Env, child_account: AccountId) -> i128 {
-    env.storage().get(DataKey::WithdAllow(child_account)).unwrap_or(Ok(0)).unwrap()
+pub fn read_nonce(env: &Env, child_account: AccountId) -> i128 {
+    env.storage().get(DataKey::Nonce(child_account)).unwrap_or(Ok(0)).unwrap()
 }
 
 
@@ -89,13 +603,853 @@ pub fn read_withdrawn_allowance(env: &Env, child_account: AccountId) -> i128 {
 // Aux Functions
 //
 
-pub fn calculate_allowance_available(env: &Env, 
-                                     start_period: u64, 
-                                     step_period: u64, 
-                                     child_allowance: i128, 
-                                     withdrawn_allowance: i128) -> i128{
-    let seconds_elapsed = env.ledger().timestamp() - start_period;
-    return ((seconds_elapsed / step_period)as i128 * child_allowance) - withdrawn_allowance;
+pub fn is_ongoing(pauses: &Vec<Pause>) -> bool {
+    match pauses.last() {
+        Some(pause) => pause.unwrap().end == 0,
+        None => false,
+    }
+}
+
+// Opens a new pause at the current timestamp
+pub fn start_pause(env: &Env, pauses: &mut Vec<Pause>) {
+    pauses.push_back(Pause {
+        start: env.ledger().timestamp(),
+        end: 0,
+    });
+}
+
+// Closes the ongoing pause at the current timestamp
+pub fn end_pause(env: &Env, pauses: &mut Vec<Pause>) {
+    let last = pauses.len() - 1;
+    let mut pause = pauses.get_unchecked(last).unwrap();
+    pause.end = env.ledger().timestamp();
+    pauses.set(last, pause);
+}
+
+// Measures the period two periods have in common, e.g. in seconds
+fn overlap(
+    a_start: u64,
+    a_end: u64,
+    b_start: u64,
+    b_end: u64,
+    measure: &dyn Fn(u64, u64) -> u64,
+) -> u64 {
+    let start = a_start.max(b_start);
+    let end = a_end.min(b_end);
+    if end > start {
+        return measure(start, end);
+    }
+    0
+}
+
+// Measures the time between 'from' and 'to' in which the contract was paused or the child
+// suspended. Periods in which both happened at the same time are only counted once.
+fn paused_time(
+    pauses: &Vec<Pause>,
+    suspensions: &Vec<Pause>,
+    from: u64,
+    to: u64,
+    measure: &dyn Fn(u64, u64) -> u64,
+) -> u64 {
+    let mut paused = 0;
+    for pause in pauses.iter_unchecked() {
+        let pause_end = if pause.end == 0 { to } else { pause.end };
+        paused += overlap(pause.start, pause_end, from, to, measure);
+
+        for suspension in suspensions.iter_unchecked() {
+            let suspension_end = if suspension.end == 0 { to } else { suspension.end };
+            paused -= overlap(
+                pause.start.max(suspension.start),
+                pause_end.min(suspension_end),
+                from,
+                to,
+                measure,
+            );
+        }
+    }
+    for suspension in suspensions.iter_unchecked() {
+        let suspension_end = if suspension.end == 0 { to } else { suspension.end };
+        paused += overlap(suspension.start, suspension_end, from, to, measure);
+    }
+    paused
+}
+
+const SECONDS_PER_DAY: u64 = 86400;
+
+// Converts days since the unix epoch into a (year, month, day) UTC date
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Counts the calendar steps falling from the unix epoch up to a given timestamp.
+// Steps on a day past the end of a month fall on its last day instead.
+pub fn count_calendar_steps(calendar: &Calendar, timestamp: u64) -> u64 {
+    let days = timestamp / SECONDS_PER_DAY;
+    match calendar {
+        Calendar::Seconds => 0,
+        Calendar::Monthly(day) => {
+            let (year, month, month_day) = civil_from_days(days);
+            let step_day = (*day as u64).min(days_in_month(year, month));
+            let months = (year - 1970) * 12 + month - 1;
+            if month_day >= step_day {
+                months + 1
+            } else {
+                months
+            }
+        }
+        Calendar::Weekly(weekday) => {
+            // The epoch fell on a Thursday, so this is the first day on the weekday
+            let first_day = (*weekday as u64 + 3) % 7;
+            (days + 7 - first_day) / 7
+        }
+    }
+}
+
+// Caps a timestamp to the period in which the schedule accrues, or None before its start.
+// Allowance stops accruing at the end_period, but whatever was earned up to it remains
+// available to be claimed.
+fn accrual_timestamp(schedule: &Schedule, timestamp: u64) -> Option<u64> {
+    let mut timestamp = timestamp;
+    if schedule.end > 0 && timestamp > schedule.end {
+        timestamp = schedule.end;
+    }
+
+    if timestamp < schedule.start {
+        return None;
+    }
+    Some(timestamp)
+}
+
+// Counts how many seconds of the schedule have elapsed up to a given timestamp,
+// leaving out the time in which accrual was paused
+fn count_seconds(
+    schedule: &Schedule,
+    pauses: &Vec<Pause>,
+    suspensions: &Vec<Pause>,
+    timestamp: u64,
+) -> u64 {
+    match accrual_timestamp(schedule, timestamp) {
+        Some(timestamp) => (timestamp - schedule.start).saturating_sub(paused_time(
+            pauses,
+            suspensions,
+            schedule.start,
+            timestamp,
+            &|from, to| to - from,
+        )),
+        None => 0,
+    }
+}
+
+// Counts how many full steps of the schedule have elapsed up to a given timestamp,
+// leaving out the time in which accrual was paused. Calendar steps falling while
+// paused are skipped, so the next ones still fall on their day.
+fn count_completed_steps(
+    schedule: &Schedule,
+    pauses: &Vec<Pause>,
+    suspensions: &Vec<Pause>,
+    timestamp: u64,
+) -> u64 {
+    let timestamp = match accrual_timestamp(schedule, timestamp) {
+        Some(timestamp) => timestamp,
+        None => return 0,
+    };
+    if schedule.calendar != Calendar::Seconds {
+        let calendar_steps = |from: u64, to: u64| {
+            count_calendar_steps(&schedule.calendar, to)
+                - count_calendar_steps(&schedule.calendar, from)
+        };
+        return calendar_steps(schedule.start, timestamp).saturating_sub(paused_time(
+            pauses,
+            suspensions,
+            schedule.start,
+            timestamp,
+            &calendar_steps,
+        ));
+    }
+
+    count_seconds(schedule, pauses, suspensions, timestamp) / schedule.step
+}
+
+// Counts how many steps of the schedule are paid up to a given timestamp. Paid at the
+// start of their period, each step is paid as soon as the previous one is completed,
+// the first one right at the start, unless the schedule ended along with it.
+fn count_steps(
+    schedule: &Schedule,
+    pauses: &Vec<Pause>,
+    suspensions: &Vec<Pause>,
+    timestamp: u64,
+    payout: &Payout,
+) -> u64 {
+    let steps = count_completed_steps(schedule, pauses, suspensions, timestamp);
+    if *payout == Payout::End || accrual_timestamp(schedule, timestamp).is_none() {
+        return steps;
+    }
+
+    let ended_with_step = schedule.end > 0
+        && timestamp >= schedule.end
+        && count_completed_steps(schedule, pauses, suspensions, schedule.end)
+            > count_completed_steps(schedule, pauses, suspensions, schedule.end - 1);
+    if ended_with_step {
+        return steps;
+    }
+    steps + 1
+}
+
+// Checks if the allowance is prorated per second. Calendar steps are always paid in full.
+fn is_streaming(schedule: &Schedule, accrual: &Accrual) -> bool {
+    accrual.streaming && schedule.calendar == Calendar::Seconds
+}
+
+// Counts the steps paid after the last checkpoint up to now. Streamed steps
+// are counted once completed, whenever they are paid.
+fn count_steps_since(
+    env: &Env,
+    schedule: &Schedule,
+    checkpoint: &Checkpoint,
+    pauses: &Vec<Pause>,
+    suspensions: &Vec<Pause>,
+    accrual: &Accrual,
+) -> u64 {
+    let payout = if is_streaming(schedule, accrual) {
+        &Payout::End
+    } else {
+        &accrual.payout
+    };
+    count_steps(schedule, pauses, suspensions, env.ledger().timestamp(), payout)
+        .saturating_sub(count_steps(schedule, pauses, suspensions, checkpoint.timestamp, payout))
+}
+
+// Allowance paid by the schedule from its start up to a given timestamp. Streaming
+// prorates the step in progress per second, rounded down, while otherwise only whole
+// steps are paid, either at the start or at the end of their period.
+pub fn calculate_allowance_paid(
+    env: &Env,
+    schedule: &Schedule,
+    pauses: &Vec<Pause>,
+    suspensions: &Vec<Pause>,
+    timestamp: u64,
+    accrual: &Accrual,
+) -> i128 {
+    if is_streaming(schedule, accrual) {
+        let seconds = count_seconds(schedule, pauses, suspensions, timestamp);
+        return checked(
+            env,
+            schedule
+                .amount
+                .checked_mul(i128::from(seconds))
+                .map(|paid| paid / i128::from(schedule.step)),
+        );
+    }
+
+    let steps = count_steps(schedule, pauses, suspensions, timestamp, &accrual.payout);
+    checked(env, i128::from(steps).checked_mul(schedule.amount))
+}
+
+// Allowance paid by the schedule after the last checkpoint up to now. Both ends are
+// rounded down from the start, so the rounding never adds up across checkpoints.
+fn calculate_allowance_paid_since(
+    env: &Env,
+    schedule: &Schedule,
+    checkpoint: &Checkpoint,
+    pauses: &Vec<Pause>,
+    suspensions: &Vec<Pause>,
+    accrual: &Accrual,
+) -> i128 {
+    let now = env.ledger().timestamp();
+    let paid = calculate_allowance_paid(env, schedule, pauses, suspensions, now, accrual);
+    let paid_before = calculate_allowance_paid(
+        env,
+        schedule,
+        pauses,
+        suspensions,
+        checkpoint.timestamp,
+        accrual,
+    );
+    checked(env, paid.checked_sub(paid_before))
+}
+
+// Total allowance accrued so far. Only what was paid after the last checkpoint
+// follows the current schedule amount.
+pub fn calculate_allowance_accrued(
+    env: &Env,
+    schedule: &Schedule,
+    checkpoint: &Checkpoint,
+    pauses: &Vec<Pause>,
+    suspensions: &Vec<Pause>,
+    accrual: &Accrual,
+) -> i128 {
+    let paid =
+        calculate_allowance_paid_since(env, schedule, checkpoint, pauses, suspensions, accrual);
+    checked(env, checkpoint.accrued.checked_add(paid))
+}
+
+// Fixed point precision of the interest growth, 18 decimals
+pub const INTEREST_SCALE: i128 = 1_000_000_000_000_000_000;
+
+// Multiplies two non negative fixed point numbers, rounded down. Both are split into
+// their whole and fractional parts, so it only overflows if the result does.
+fn mul_scaled(x: i128, y: i128) -> Option<i128> {
+    let (x_whole, x_fraction) = (x / INTEREST_SCALE, x % INTEREST_SCALE);
+    let (y_whole, y_fraction) = (y / INTEREST_SCALE, y % INTEREST_SCALE);
+    x_whole
+        .checked_mul(y)?
+        .checked_add(x_fraction.checked_mul(y_whole)?)?
+        .checked_add(x_fraction * y_fraction / INTEREST_SCALE)
+}
+
+// How a balance grows over a number of steps compounding an interest in basis points,
+// in fixed point: n steps turn a balance b into b * growth + amount * added, where
+// amount is added at the end of each step. It's computed by squaring, so it takes
+// a bounded number of multiplications however many steps there are.
+fn calculate_growth(steps: u64, interest_bps: u32) -> Option<(i128, i128)> {
+    // The steps of 'first' followed by the ones of 'then'
+    let compose = |first: (i128, i128), then: (i128, i128)| -> Option<(i128, i128)> {
+        Some((
+            mul_scaled(first.0, then.0)?,
+            mul_scaled(first.1, then.0)?.checked_add(then.1)?,
+        ))
+    };
+
+    let mut growth = (INTEREST_SCALE, 0);
+    let mut power = (
+        INTEREST_SCALE / 10000 * (10000 + i128::from(interest_bps)),
+        INTEREST_SCALE,
+    );
+    let mut steps = steps;
+    while steps > 0 {
+        if steps & 1 == 1 {
+            growth = compose(growth, power)?;
+        }
+        steps >>= 1;
+        if steps > 0 {
+            power = compose(power, power)?;
+        }
+    }
+    Some(growth)
+}
+
+// Balance after a number of steps, along with the fraction of a unit it holds on top
+// of it. Each step first earns interest over the balance held through it, and then
+// adds its own allowance amount. Only the fraction is rounded down, to INTEREST_SCALE,
+// so splitting the steps anywhere while carrying it along gives the same balance. A
+// balance in debt doesn't earn any interest until it's paid back.
+pub fn accrue_with_interest(
+    balance: i128,
+    fraction: i128,
+    amount: i128,
+    steps: u64,
+    interest_bps: u32,
+) -> Option<(i128, i128)> {
+    let mut balance = balance;
+    let mut steps = steps;
+    if balance < 0 {
+        if amount <= 0 {
+            return Some((balance, 0));
+        }
+
+        // The steps it takes the amounts added to pay the debt back
+        let debt_steps = balance.checked_neg()?.checked_add(amount - 1)? / amount;
+        if debt_steps >= i128::from(steps) {
+            return Some((i128::from(steps).checked_mul(amount)?.checked_add(balance)?, 0));
+        }
+        balance = debt_steps.checked_mul(amount)?.checked_add(balance)?;
+        steps -= debt_steps as u64;
+    }
+    if steps == 0 || (balance == 0 && fraction == 0 && amount == 0) {
+        return Some((balance, fraction));
+    }
+
+    let (growth, added) = calculate_growth(steps, interest_bps)?;
+    let scaled = balance
+        .checked_mul(growth)?
+        .checked_add(mul_scaled(fraction, growth)?)?
+        .checked_add(amount.checked_mul(added)?)?;
+    Some((scaled / INTEREST_SCALE, scaled % INTEREST_SCALE))
+}
+
+// Allowance available, accrued but not withdrawn yet, along with the fraction of a
+// unit earned on top of it. With interest, the unspent balance earns it at each step
+// paid after the last checkpoint. It's None if too big to be calculated.
+fn calculate_balance(
+    env: &Env,
+    schedule: &Schedule,
+    checkpoint: &Checkpoint,
+    pauses: &Vec<Pause>,
+    suspensions: &Vec<Pause>,
+    withdrawn_allowance: i128,
+    accrual: &Accrual,
+) -> Option<(i128, i128)> {
+    if accrual.interest_bps == 0 {
+        let accrued =
+            calculate_allowance_accrued(env, schedule, checkpoint, pauses, suspensions, accrual);
+        return Some((accrued.checked_sub(withdrawn_allowance)?, 0));
+    }
+
+    // Whole steps compound the interest, while whatever was streamed
+    // on top of them is just added along
+    let steps = count_steps_since(env, schedule, checkpoint, pauses, suspensions, accrual);
+    let paid =
+        calculate_allowance_paid_since(env, schedule, checkpoint, pauses, suspensions, accrual);
+    let (balance, fraction) = accrue_with_interest(
+        checkpoint.accrued.checked_sub(withdrawn_allowance)?,
+        checkpoint.fraction,
+        schedule.amount,
+        steps,
+        accrual.interest_bps,
+    )?;
+    let streamed = paid.checked_sub(i128::from(steps).checked_mul(schedule.amount)?)?;
+    Some((balance.checked_add(streamed)?, fraction))
+}
+
+// Allowance available, accrued but not withdrawn yet. It's None if too big to be calculated.
+pub fn calculate_allowance_available(
+    env: &Env,
+    schedule: &Schedule,
+    checkpoint: &Checkpoint,
+    pauses: &Vec<Pause>,
+    suspensions: &Vec<Pause>,
+    withdrawn_allowance: i128,
+    accrual: &Accrual,
+) -> Option<i128> {
+    calculate_balance(
+        env,
+        schedule,
+        checkpoint,
+        pauses,
+        suspensions,
+        withdrawn_allowance,
+        accrual,
+    )
+    .map(|(available, _)| available)
+}
+
+// Unwraps the result of a checked operation, failing with an Overflow
+// error when it went out of bounds
+pub fn checked<T>(env: &Env, value: Option<T>) -> T {
+    match value {
+        Some(value) => value,
+        None => panic_with_error!(env, Error::Overflow),
+    }
+}
+
+// The most a child can hold unclaimed under a carryover policy, if capped
+pub fn carryover_cap(schedule: &Schedule, carryover: &Carryover) -> Option<i128> {
+    match carryover {
+        Carryover::Unlimited => None,
+        Carryover::Amount(amount) => Some(*amount),
+        // A cap too big to be calculated doesn't cap anything
+        Carryover::Steps(steps) => i128::from(*steps).checked_mul(schedule.amount),
+        Carryover::None => Some(schedule.amount),
+    }
+}
+
+// Limits the available allowance to what the carryover policy keeps. Credits on
+// top of it, not withdrawn yet, aren't capped and are kept in full.
+pub fn apply_carryover(
+    available: i128,
+    credits: i128,
+    schedule: &Schedule,
+    carryover: &Carryover,
+) -> i128 {
+    let credits = available.min(credits).max(0);
+    match carryover_cap(schedule, carryover) {
+        Some(cap) if available - credits > cap => cap + credits,
+        _ => available,
+    }
+}
+
+// Allowance available for a child in a token. Once the claim window
+// is over, nothing can be withdrawn anymore.
+pub fn read_allowance_available(env: &Env, child_account: AccountId, token: BytesN<32>) -> i128 {
+    let schedule = read_schedule(env, child_account.clone(), token.clone());
+    if is_claim_window_closed(env, schedule.end, read_claim_window(env)) {
+        return 0;
+    }
+
+    let checkpoint = read_checkpoint(env, child_account.clone(), token.clone());
+    let pauses = read_pauses(env);
+    let suspensions = read_suspensions(env, child_account.clone());
+    let withdrawn_allowance = read_withdrawn_allowance(env, child_account.clone(), token.clone());
+    let accrual = read_accrual(env, child_account.clone(), token.clone());
+    let credits = read_credits(env, child_account.clone(), token.clone());
+    let carryover = read_carryover(env, child_account, token);
+
+    let available = checked(
+        env,
+        calculate_allowance_available(
+            env,
+            &schedule,
+            &checkpoint,
+            &pauses,
+            &suspensions,
+            withdrawn_allowance,
+            &accrual,
+        ),
+    );
+    apply_carryover(available, credits, &schedule, &carryover)
+}
+
+// Checks a child can withdraw an amount in a token, keeping the total it withdrew within
+// the total it accrued plus its advance limit. Expects the allowance to be checkpointed
+// right before, and returns the new total withdrawn.
+pub fn check_withdrawable(
+    env: &Env,
+    child_account: AccountId,
+    token: BytesN<32>,
+    amount: i128,
+) -> Result<i128, Error> {
+    let accrued = read_checkpoint(env, child_account.clone(), token.clone()).accrued;
+    let advance_limit = read_advance_limit(env, child_account.clone(), token.clone());
+    let withdrawn = read_withdrawn_allowance(env, child_account, token)
+        .checked_add(amount)
+        .ok_or(Error::Overflow)?;
+
+    match accrued.checked_add(advance_limit) {
+        Some(limit) if withdrawn <= limit => Ok(withdrawn),
+        Some(_) => Err(Error::InsufficientAllowance),
+        None => Err(Error::Overflow),
+    }
+}
+
+// Amount withdrawn in advance by a child in a token, not paid back yet
+pub fn read_debt(env: &Env, child_account: AccountId, token: BytesN<32>) -> i128 {
+    let available = read_allowance_available(env, child_account, token);
+    if available < 0 {
+        return -available;
+    }
+    0
+}
+
+// Total allowance in a token available to all children, but not withdrawn yet
+pub fn read_allowance_outstanding(env: &Env, token: BytesN<32>) -> i128 {
+    let mut outstanding: i128 = 0;
+    for child_account in read_children(env).iter_unchecked() {
+        if has_allowance(env, child_account.clone(), token.clone()) {
+            let available = read_allowance_available(env, child_account.clone(), token.clone());
+            if available > 0 {
+                outstanding = checked(env, outstanding.checked_add(available));
+            }
+        }
+
+        // Savings locked in goals are owed as well
+        for goal in read_goals(env, child_account).iter_unchecked() {
+            if goal.token == token && !goal.released {
+                outstanding = checked(
+                    env,
+                    calculate_goal_balance(&goal).and_then(|balance| outstanding.checked_add(balance)),
+                );
+            }
+        }
+    }
+    outstanding
+}
+
+// Funds in a token backing the allowances. In escrow mode that's the contract's
+// own balance, otherwise what the parent both approved to the contract and holds.
+pub fn read_funding(env: &Env, token: BytesN<32>) -> i128 {
+    let token_client = token::Client::new(env, token);
+    let contract = Identifier::Contract(env.get_current_contract());
+    if read_escrow(env) {
+        return token_client.balance(&contract);
+    }
+
+    let parent = Identifier::Account(read_admin(env));
+    token_client
+        .allowance(&parent, &contract)
+        .min(token_client.balance(&parent))
+}
+
+// Totals what is owed to all children in a token against the parent's approval
+// to the contract, the parent's balance and the funds held in escrow
+pub fn read_funding_status(env: &Env, token: BytesN<32>) -> Funding {
+    let token_client = token::Client::new(env, token.clone());
+    let contract = Identifier::Contract(env.get_current_contract());
+    let parent = Identifier::Account(read_admin(env));
+
+    Funding {
+        owed: read_allowance_outstanding(env, token),
+        approved: token_client.allowance(&parent, &contract),
+        balance: token_client.balance(&parent),
+        escrowed: token_client.balance(&contract),
+    }
+}
+
+// Makes sure the funds paying out a withdraw are there before calling the token,
+// so the child gets a meaningful error instead of a failure inside the token contract
+pub fn check_funding(env: &Env, token: BytesN<32>, amount: i128) -> Result<(), Error> {
+    let token_client = token::Client::new(env, token);
+    let contract = Identifier::Contract(env.get_current_contract());
+    if read_escrow(env) {
+        if token_client.balance(&contract) < amount {
+            return Err(Error::EscrowInsufficient);
+        }
+        return Ok(());
+    }
+
+    let parent = Identifier::Account(read_admin(env));
+    if token_client.allowance(&parent, &contract) < amount {
+        return Err(Error::ParentApprovalInsufficient);
+    }
+    if token_client.balance(&parent) < amount {
+        return Err(Error::ParentBalanceInsufficient);
+    }
+    Ok(())
+}
+
+// Gathers the allowance of every registered child in every token it is paid in
+pub fn read_summary(env: &Env) -> Vec<ChildSumm> {
+    let mut summary = Vec::new(env);
+    for child_account in read_children(env).iter_unchecked() {
+        for token in read_tokens(env).iter_unchecked() {
+            if !has_allowance(env, child_account.clone(), token.clone()) {
+                continue;
+            }
+
+            summary.push_back(ChildSumm {
+                child: child_account.clone(),
+                token: token.clone(),
+                rate: read_allowance(env, child_account.clone(), token.clone()),
+                withdrawn: read_withdrawn_allowance(env, child_account.clone(), token.clone()),
+                available: read_allowance_available(env, child_account.clone(), token.clone()),
+                schedule: read_schedule(env, child_account.clone(), token),
+            });
+        }
+    }
+    summary
+}
+
+// Makes sure a withdraw respects the spending limits of the child in the token
+pub fn check_limits(
+    env: &Env,
+    child_account: AccountId,
+    token: BytesN<32>,
+    amount: i128,
+) -> Result<(), Error> {
+    let limits = read_limits(env, child_account.clone(), token.clone());
+    if limits.per_draw > 0 && amount > limits.per_draw {
+        return Err(Error::DrawLimitExceeded);
+    }
+
+    let now = env.ledger().timestamp();
+    let draws = read_draws(env, child_account, token);
+    if let Some(last) = draws.last() {
+        if limits.interval > 0 && now < last.unwrap().timestamp.saturating_add(limits.interval) {
+            return Err(Error::DrawTooSoon);
+        }
+    }
+
+    if limits.per_window > 0 {
+        let mut drawn = amount;
+        for draw in draws.iter_unchecked() {
+            if draw.timestamp.saturating_add(limits.window) > now {
+                drawn = drawn.checked_add(draw.amount).ok_or(Error::Overflow)?;
+            }
+        }
+        if drawn > limits.per_window {
+            return Err(Error::WindowLimitExceeded);
+        }
+    }
+    Ok(())
+}
+
+// Records a withdraw of the child in the token, keeping only the ones still
+// within its rolling window along with the last one
+pub fn record_draw(env: &Env, child_account: AccountId, token: BytesN<32>, amount: i128) {
+    let now = env.ledger().timestamp();
+    let limits = read_limits(env, child_account.clone(), token.clone());
+    let mut draws = Vec::new(env);
+    for draw in read_draws(env, child_account.clone(), token.clone()).iter_unchecked() {
+        if draw.timestamp.saturating_add(limits.window) > now {
+            draws.push_back(draw);
+        }
+    }
+    draws.push_back(Draw {
+        amount,
+        timestamp: now,
+    });
+    write_draws(env, child_account, token, draws);
+}
+
+// Pays an amount of a token out to a child, from the escrow in escrow mode
+// or otherwise pulled from the parent
+pub fn pay_child(env: &Env, child_account: AccountId, token: BytesN<32>, amount: i128) {
+    let token_client = token::Client::new(env, token);
+    if read_escrow(env) {
+        token_client.xfer(
+            &Signature::Invoker,
+            &0,
+            &Identifier::Account(child_account),
+            &amount,
+        );
+    } else {
+        token_client.xfer_from(
+            &Signature::Invoker,
+            &0,
+            &Identifier::Account(read_admin(env)),
+            &Identifier::Account(child_account),
+            &amount,
+        );
+    }
+}
+
+// Stores the allowance accrued by a child in a token up to now, so a schedule
+// change only affects the steps completed from this point on. Whatever is over
+// the carryover policy is forfeited, so it doesn't come back once withdrawn.
+pub fn checkpoint_allowance(env: &Env, child_account: AccountId, token: BytesN<32>) {
+    let checkpoint = checked(env, calculate_checkpoint(env, child_account.clone(), token.clone()));
+    write_checkpoint(env, child_account, token, checkpoint);
+}
+
+// Allowance accrued by a child in a token up to now, as checkpointed by
+// checkpoint_allowance. It's None if too big to be calculated.
+pub fn calculate_checkpoint(
+    env: &Env,
+    child_account: AccountId,
+    token: BytesN<32>,
+) -> Option<Checkpoint> {
+    let schedule = read_schedule(env, child_account.clone(), token.clone());
+    let checkpoint = read_checkpoint(env, child_account.clone(), token.clone());
+    let pauses = read_pauses(env);
+    let suspensions = read_suspensions(env, child_account.clone());
+    let withdrawn = read_withdrawn_allowance(env, child_account.clone(), token.clone());
+    let accrual = read_accrual(env, child_account.clone(), token.clone());
+    let (available, fraction) = calculate_balance(
+        env,
+        &schedule,
+        &checkpoint,
+        &pauses,
+        &suspensions,
+        withdrawn,
+        &accrual,
+    )?;
+
+    // The fraction of a unit is forfeited along with whatever is over the carryover policy
+    let credits = read_credits(env, child_account.clone(), token.clone());
+    let carryover = read_carryover(env, child_account, token);
+    let kept = apply_carryover(available, credits, &schedule, &carryover);
+    Some(Checkpoint {
+        accrued: withdrawn.checked_add(kept)?,
+        timestamp: env.ledger().timestamp(),
+        fraction: if kept < available { 0 } else { fraction },
+    })
+}
+
+// Starts the allowance of a child newly registered in a token from now on, so it isn't
+// paid for the steps before it, nor again for what it withdrew if it was ever removed
+pub fn checkpoint_registration(env: &Env, child_account: AccountId, token: BytesN<32>) {
+    write_checkpoint(
+        env,
+        child_account,
+        token,
+        Checkpoint {
+            accrued: 0,
+            timestamp: env.ledger().timestamp(),
+            fraction: 0,
+        },
+    );
+}
+
+// What a goal pays out: the amount saved plus the parent's match over it,
+// in basis points rounded down. It's None if too big to be calculated.
+pub fn calculate_goal_balance(goal: &Goal) -> Option<i128> {
+    goal.saved
+        .checked_mul(i128::from(goal.match_bps))
+        .and_then(|matched| goal.saved.checked_add(matched / 10000))
+}
+
+// Checks if a goal can be released, either by reaching its target or its unlock timestamp
+pub fn is_goal_unlocked(env: &Env, goal: &Goal) -> bool {
+    calculate_goal_balance(goal).is_none_or(|balance| balance >= goal.target)
+        || (goal.unlock > 0 && env.ledger().timestamp() >= goal.unlock)
+}
+
+// Switches how the allowance of a child in a token is paid, streaming or at either end
+// of each step. The step in progress is rebased on the new mode, so it's neither paid
+// twice nor lost: e.g. switching out of streaming takes back what was streamed of it,
+// paid in full once the step is completed.
+pub fn switch_accrual(env: &Env, child_account: AccountId, token: BytesN<32>, accrual: Accrual) {
+    checkpoint_allowance(env, child_account.clone(), token.clone());
+
+    let schedule = read_schedule(env, child_account.clone(), token.clone());
+    let pauses = read_pauses(env);
+    let suspensions = read_suspensions(env, child_account.clone());
+    let now = env.ledger().timestamp();
+    let previous = read_accrual(env, child_account.clone(), token.clone());
+    let paid_before =
+        calculate_allowance_paid(env, &schedule, &pauses, &suspensions, now, &previous);
+    let paid = calculate_allowance_paid(env, &schedule, &pauses, &suspensions, now, &accrual);
+
+    let mut checkpoint = read_checkpoint(env, child_account.clone(), token.clone());
+    checkpoint.accrued = checked(
+        env,
+        paid.checked_sub(paid_before)
+            .and_then(|rebase| checkpoint.accrued.checked_add(rebase)),
+    );
+    write_checkpoint(env, child_account.clone(), token.clone(), checkpoint);
+    write_streaming(env, child_account.clone(), token.clone(), accrual.streaming);
+    write_payout(env, child_account, token, accrual.payout);
+}
+
+// Credits an amount on top of what a child accrued in a token, so it's
+// available to withdraw along with the allowance, outside its carryover policy
+pub fn credit_allowance(env: &Env, child_account: AccountId, token: BytesN<32>, amount: i128) {
+    checkpoint_allowance(env, child_account.clone(), token.clone());
+    let mut checkpoint = read_checkpoint(env, child_account.clone(), token.clone());
+    checkpoint.accrued = checked(env, checkpoint.accrued.checked_add(amount));
+    write_checkpoint(env, child_account.clone(), token.clone(), checkpoint);
+
+    let credits = read_credits(env, child_account.clone(), token.clone());
+    write_credits(env, child_account, token, checked(env, credits.checked_add(amount)));
+}
+
+// Takes what a child withdrew in a token out of its credits once the rest of
+// the allowance is used up, so they're the last part of it to be withdrawn.
+// Expects the allowance to be checkpointed right before the withdrawal.
+pub fn spend_credits(env: &Env, child_account: AccountId, token: BytesN<32>) {
+    let credits = read_credits(env, child_account.clone(), token.clone());
+    if credits == 0 {
+        return;
+    }
+
+    let accrued = read_checkpoint(env, child_account.clone(), token.clone()).accrued;
+    let withdrawn = read_withdrawn_allowance(env, child_account.clone(), token.clone());
+    let available = accrued.checked_sub(withdrawn).unwrap_or(0).max(0);
+    if available < credits {
+        write_credits(env, child_account, token, available);
+    }
+}
+
+// Checks if the claim window after the end_period is already over.
+// A claim_window of 0 keeps the accrued allowance claimable indefinitely.
+pub fn is_claim_window_closed(env: &Env, end_period: u64, claim_window: u64) -> bool {
+    end_period > 0
+        && claim_window > 0
+        && end_period
+            .checked_add(claim_window)
+            .is_some_and(|closed| env.ledger().timestamp() > closed)
 }
 
 fn to_account(address: Address) -> Result<AccountId, Error> {
@@ -103,4 +1457,91 @@ fn to_account(address: Address) -> Result<AccountId, Error> {
         Address::Account(id) => Ok(id),
         _ => Err(Error::InvalidInvoker),
     }
+}
+
+// Resolves the invoker, making sure it's one of the guardians
+pub fn read_invoker_guardian(env: &Env) -> Result<AccountId, Error> {
+    let invoker = to_account(env.invoker())?;
+    if !read_guardians(env).accounts.contains(&invoker) {
+        return Err(Error::NotAuthorized);
+    }
+    Ok(invoker)
+}
+
+// Hands the admin role over to a new account. If the previous admin was a
+// guardian, the new one takes its place among the guardians. If the new one
+// already was a guardian, the threshold is kept within the remaining guardians.
+pub fn transfer_admin(env: &Env, new_admin: AccountId) {
+    let admin = read_admin(env);
+    let mut guardians = read_guardians(env);
+    if let Some(index) = guardians.accounts.first_index_of(&admin) {
+        if guardians.accounts.contains(&new_admin) {
+            guardians.accounts.remove_unchecked(index);
+            guardians.threshold = guardians.threshold.min(guardians.accounts.len());
+        } else {
+            guardians.accounts.set(index, new_admin.clone());
+        }
+        write_guardians(env, guardians);
+    }
+
+    write_admin(env, new_admin);
+    remove_admin_proposal(env);
+}
+
+// Records the approval of a guardian for a sensitive action. Returns true once
+// it's approved by enough of the current guardians, clearing its approvals.
+pub fn approve_action(env: &Env, guardian: AccountId, action: Action) -> bool {
+    let guardians = read_guardians(env);
+    let mut approvals = read_approvals(env, action.clone());
+    if !approvals.contains(&guardian) {
+        approvals.push_back(guardian);
+    }
+
+    // Approvals from accounts that stopped being guardians don't count
+    let mut count = 0;
+    for approver in approvals.iter_unchecked() {
+        if guardians.accounts.contains(&approver) {
+            count += 1;
+        }
+    }
+
+    if count >= guardians.threshold {
+        env.storage().remove(DataKey::Approvals(action));
+        return true;
+    }
+    write_approvals(env, action, approvals);
+    false
+}
+
+// Resolves the account that authorized the call through the given signature.
+// Only user accounts are accepted, since children are registered by AccountId.
+pub fn read_signer_account(env: &Env, sig: &Signature) -> Result<AccountId, Error> {
+    match sig {
+        Signature::Invoker => to_account(env.invoker()),
+        Signature::Account(account_sig) => Ok(account_sig.account_id.clone()),
+        Signature::Ed25519(_) => Err(Error::InvalidInvoker),
+    }
+}
+
+// Replay protection for signed calls. An invoker signature can't be replayed,
+// so it must always use nonce 0 and doesn't consume it.
+pub fn verify_and_consume_nonce(
+    env: &Env,
+    sig: &Signature,
+    child_account: AccountId,
+    nonce: i128,
+) -> Result<(), Error> {
+    if let Signature::Invoker = sig {
+        if nonce != 0 {
+            return Err(Error::InvalidNonce);
+        }
+        return Ok(());
+    }
+
+    let expected_nonce = read_nonce(env, child_account.clone());
+    if nonce != expected_nonce {
+        return Err(Error::InvalidNonce);
+    }
+    write_nonce(env, child_account, expected_nonce + 1);
+    Ok(())
 }
\ No newline at end of file
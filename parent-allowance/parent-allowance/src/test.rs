@@ -1,12 +1,18 @@
 #![cfg(test)]
 
 
-use crate::context::{DataKey, State};
+use crate::context::{
+    AdminProp, Calendar, Carryover, DataKey, Guardians, Limits, Payout, ReqStatus, Schedule, State,
+};
 use crate::contract::{token, ParentAllowance, ParentAllowanceClient};
-use soroban_auth::{Identifier, Signature};
+use crate::errors::Error;
+use crate::services::{accrue_with_interest, count_calendar_steps, INTEREST_SCALE};
+use soroban_auth::testutils::ed25519;
+use soroban_auth::{AccountSignatures, Identifier, Signature, SignaturePayload};
 use soroban_sdk::{
-    testutils::{Accounts, Ledger, LedgerInfo},
-    AccountId, BytesN, Env, IntoVal,
+    symbol,
+    testutils::{ed25519::Sign, Accounts, Events, Ledger, LedgerInfo},
+    vec, AccountId, Bytes, BytesN, Env, IntoVal, RawVal, Symbol, Vec,
 };
 
 fn create_token_contract(
@@ -29,6 +35,7 @@ fn create_token_contract(
     (token_contract_id.clone(), token_client)
 }
 
+#[allow(clippy::needless_borrow, clippy::needless_return)]
 fn updates_contract_time(env: &Env, contract_id: BytesN<32>, time: u64) -> ParentAllowanceClient {
     env.ledger().set(LedgerInfo {
         timestamp: time,
@@ -40,6 +47,43 @@ fn updates_contract_time(env: &Env, contract_id: BytesN<32>, time: u64) -> Paren
     return ParentAllowanceClient::new(&env, &contract_id);
 }
 
+// Signs a call to the allowance contract on behalf of an account, with a key that was added as its signer
+fn sign_as_account(
+    env: &Env,
+    account: &AccountId,
+    signer: &(impl ed25519::Identifier + Sign<SignaturePayload, Signature = [u8; 64]>),
+    contract_id: &BytesN<32>,
+    name: Symbol,
+    args: impl IntoVal<Env, Vec<RawVal>>,
+) -> Signature {
+    match ed25519::sign(env, signer, contract_id, name, args) {
+        Signature::Ed25519(signature) => Signature::Account(AccountSignatures {
+            account_id: account.clone(),
+            signatures: vec![env, signature],
+        }),
+        _ => panic!("expected an ed25519 signature"),
+    }
+}
+
+// Small xorshift generator, so the randomized tests can be replayed from their seed
+fn next_random(seed: &mut u64) -> u64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    *seed
+}
+
+// Collects the events published by the allowance contract, leaving out the token ones
+fn contract_events(env: &Env, contract_id: &BytesN<32>) -> Vec<(Vec<RawVal>, RawVal)> {
+    let mut events = Vec::new(env);
+    for (event_contract_id, topics, data) in env.events().all().iter_unchecked() {
+        if &event_contract_id == contract_id {
+            events.push_back((topics, data));
+        }
+    }
+    events
+}
+
 //Make sure the contract cannot be initialized more than once
 #[test]
 #[should_panic(expected = "Status(ContractError(1)")]
@@ -52,7 +96,7 @@ fn test_invalid_contract_initialize_panics_when_contract_is_initialized() {
     //let admin_id = Identifier::Account(admin.clone());
     let step_period = 10;
     let start_period = 10;
-    let end_period = 10;
+    let end_period = 20;
 
     env.as_contract(&contract_id, || {
         env.storage().set(DataKey::State, State::Initiated)
@@ -69,6 +113,7 @@ fn test_invalid_contract_initialize_panics_when_contract_is_initialized() {
 
 //Test the initalized start, step and end periods
 #[test]
+#[allow(unused_assignments)]
 fn test_valid_initialized_periods() {
     let env = Env::default();
     let contract_id = env.register_contract(None, ParentAllowance);
@@ -110,7 +155,7 @@ fn test_valid_children_registration() {
     //let admin_id = Identifier::Account(admin.clone());
     let step_period = 10;
     let start_period = 10;
-    let end_period = 10;
+    let end_period = 20;
 
     client.initialize(
         &admin,
@@ -125,23 +170,24 @@ fn test_valid_children_registration() {
     //let child_a_account_id = Identifier::Account(child_a_account.clone());
     let child_a_allowance: i128 = 10;
 
-    client.with_source_account(&admin).set_allow(&child_a_account, &child_a_allowance);
+    client.with_source_account(&admin).set_allow(&child_a_account, &contract_id, &child_a_allowance);
 
-    assert_eq!(child_a_allowance, client.get_allow(&child_a_account));
+    assert_eq!(child_a_allowance, client.get_allow(&child_a_account, &contract_id));
 
     //register child b allowance and verify
     let child_b_account = env.accounts().generate();
     //let child_b_account_id = Identifier::Account(child_b_account.clone());
     let child_b_allowance: i128 = 20;
 
-    client.with_source_account(&admin).set_allow(&child_b_account, &child_b_allowance);
+    client.with_source_account(&admin).set_allow(&child_b_account, &contract_id, &child_b_allowance);
 
-    assert_eq!(child_b_allowance, client.get_allow(&child_b_account));
+    assert_eq!(child_b_allowance, client.get_allow(&child_b_account, &contract_id));
 }
 
 // Test if the contract is running correctly by adding two children,
 // waiting some time and performing withdraw with both.
 #[test]
+#[allow(unused_assignments, clippy::needless_borrow)]
 fn test_valid_sequence_withdraw() {
     let env = Env::default();
     let contract_id = env.register_contract(None, ParentAllowance);
@@ -205,27 +251,29 @@ fn test_valid_sequence_withdraw() {
     //register child a
     let child_a_account = env.accounts().generate();
     let child_a_allowance: i128 = 100;
-    client.with_source_account(&admin).set_allow(&child_a_account, &child_a_allowance);
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
 
     //register child b
     let child_b_account = env.accounts().generate();
     let child_b_allowance: i128 = 150;
-    client.with_source_account(&admin).set_allow(&child_b_account, &child_b_allowance);
+    client.with_source_account(&admin).set_allow(&child_b_account, &payment_tkn_id, &child_b_allowance);
 
     //after 1 day + 1000 seconds
     //child a withdraws 50
     client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
 
     let child_a_withdraw_amount: i128 = 50;
-    client.withdraw(&child_a_account, &child_a_withdraw_amount);
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
     assert_eq!(
         50,
-        client.get_wthdr(&child_a_account),
+        client.get_wthdr(&child_a_account, &payment_tkn_id),
         "child A withdraws 50, must have 50 withdrawn "
     );
     assert_eq!(
         50,
-        client.get_aval(&child_a_account),
+        client.get_aval(&child_a_account, &payment_tkn_id).amount,
         "child A withdraws 50, must have 50 left"
     );
 
@@ -240,10 +288,12 @@ fn test_valid_sequence_withdraw() {
     client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
 
     let child_b_withdraw_amount: i128 = 70;
-    client.withdraw(&child_b_account, &child_b_withdraw_amount);
+    client
+        .with_source_account(&child_b_account)
+        .withdraw(&Signature::Invoker, &0, &child_b_account, &payment_tkn_id, &child_b_withdraw_amount);
     assert_eq!(
         230,
-        client.get_aval(&child_b_account),
+        client.get_aval(&child_b_account, &payment_tkn_id).amount,
         "child B withdraws 70, must have 230 left"
     );
 
@@ -258,6 +308,7 @@ fn test_valid_sequence_withdraw() {
 // waiting some time and performing withdraw with both.
 #[test]
 #[should_panic(expected = "Status(ContractError(7)")]
+#[allow(unused_assignments, unused_variables, clippy::needless_borrow)]
 fn test_invalid_sequence_withdraw_panics_when_allowance_is_insufficient() {
     let env = Env::default();
     let contract_id = env.register_contract(None, ParentAllowance);
@@ -286,21 +337,92 @@ fn test_invalid_sequence_withdraw_panics_when_allowance_is_insufficient() {
     //register child a
     let child_a_account = env.accounts().generate();
     let child_a_allowance: i128 = 100;
-    client.with_source_account(&admin).set_allow(&child_a_account, &child_a_allowance);
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
 
     //after 1 day + 1000 seconds
     //child a attempts to withdraw 110
     client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
 
     let child_a_withdraw_amount: i128 = 110;
-    client.withdraw(&child_a_account, &child_a_withdraw_amount);
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
 }
 
-// Test if the contract is running correctly by adding two children,
-// waiting some time and performing withdraw with both.
+// Make sure the allowance accrued until the end period can still be withdrawn
+// during the claim window, and that it stops accruing at the end period.
+#[test]
+fn test_valid_withdraw_after_end_period_within_claim_window() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000000000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &500000000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 1669726146 + (86400 * 2) + 1000; // ends after 2 days + 1000 seconds
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //claims are accepted for 1 day after the end period
+    client.with_source_account(&admin).set_claim(&86400);
+    assert_eq!(86400, client.get_claim());
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //1000 seconds after the end period the claim window is still open
+    //child a only accrued the 2 days up to the end period
+    client = updates_contract_time(&env, contract_id.clone(), end_period + 1000);
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    let child_a_withdraw_amount: i128 = 200;
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+    assert_eq!(200, client.get_wthdr(&child_a_account, &payment_tkn_id));
+    assert_eq!(0, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //verify that parent account balance has been updated
+    assert_eq!(
+        payment_tkn.balance(&Identifier::Account(admin.clone()),),
+        1000000000 - child_a_withdraw_amount
+    );
+}
+
+// Make sure nothing can be withdrawn once the claim window after the end period is over
 #[test]
 #[should_panic(expected = "Status(ContractError(8)")]
-fn test_invalid_withdraw_panics_when_past_end_period() {
+#[allow(unused_assignments, unused_variables, clippy::needless_borrow)]
+fn test_invalid_withdraw_panics_when_past_claim_window() {
     let env = Env::default();
     let contract_id = env.register_contract(None, ParentAllowance);
     let mut client = ParentAllowanceClient::new(&env, contract_id.clone());
@@ -315,7 +437,7 @@ fn test_invalid_withdraw_panics_when_past_end_period() {
 
     let step_period = 86400; // 1 day in seconds
     let start_period = 0; // starts right away
-    let end_period = 1669800000; // future end date
+    let end_period = 1669726146 + (86400 * 2) + 1000; // ends after 2 days + 1000 seconds
 
     client.initialize(
         &admin,
@@ -325,23 +447,30 @@ fn test_invalid_withdraw_panics_when_past_end_period() {
         &end_period,
     );
 
+    //claims are accepted for 1 day after the end period
+    client.with_source_account(&admin).set_claim(&86400);
+
     //register child a
     let child_a_account = env.accounts().generate();
     let child_a_allowance: i128 = 100;
-    client.with_source_account(&admin).set_allow(&child_a_account, &child_a_allowance);
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
 
-    //after end period
-    //child a attempts to withdraw 50
-    client = updates_contract_time(&env, contract_id.clone(), 1669800000 + 1);
+    //after the claim window
+    //child a has nothing left to claim and attempts to withdraw 50
+    client = updates_contract_time(&env, contract_id.clone(), end_period + 86400 + 1);
+    assert_eq!(0, client.get_aval(&child_a_account, &payment_tkn_id).amount);
 
     let child_a_withdraw_amount: i128 = 50;
-    client.withdraw(&child_a_account, &child_a_withdraw_amount);
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
 }
 
 // Test if the contract is running correctly by adding two children,
 // waiting some time and performing withdraw with both.
 #[test]
 #[should_panic(expected = "Status(ContractError(9)")]
+#[allow(unused_assignments, unused_variables, clippy::needless_borrow)]
 fn test_invalid_withdraw_panics_when_before_start_period() {
     let env = Env::default();
     let contract_id = env.register_contract(None, ParentAllowance);
@@ -370,12 +499,3633 @@ fn test_invalid_withdraw_panics_when_before_start_period() {
     //register child a
     let child_a_account = env.accounts().generate();
     let child_a_allowance: i128 = 100;
-    client.with_source_account(&admin).set_allow(&child_a_account, &child_a_allowance);
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
 
     //before start period
     //child a attempts to withdraw 50
     client = updates_contract_time(&env, contract_id.clone(), 1669800000 - 1);
 
     let child_a_withdraw_amount: i128 = 50;
-    client.withdraw(&child_a_account, &child_a_withdraw_amount);
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+}
+
+// Make sure a third party cannot withdraw the allowance of a child
+#[test]
+#[should_panic(expected = "Status(ContractError(6)")]
+fn test_invalid_withdraw_panics_when_invoker_is_not_the_child() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //after 1 day + 1000 seconds
+    //a third party attempts to withdraw 50 on behalf of child a
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+
+    let third_party_account = env.accounts().generate();
+    let child_a_withdraw_amount: i128 = 50;
+    client
+        .with_source_account(&third_party_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+}
+
+// Make sure an invoker signature cannot be used with a nonce other than 0
+#[test]
+#[should_panic(expected = "Status(ContractError(11)")]
+fn test_invalid_withdraw_panics_when_invoker_nonce_is_not_zero() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //after 1 day + 1000 seconds
+    //child a attempts to withdraw 50 with a non zero nonce
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+
+    let child_a_withdraw_amount: i128 = 50;
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &1, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+}
+
+// Verify a child can withdraw through a signature of its account, relayed by anyone,
+// and that the signature can't be replayed nor used for another child
+#[test]
+fn test_valid_signed_withdraw() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //child a and child b sign with keys of their own accounts
+    let child_a_account = env.accounts().generate_and_create();
+    let (child_a_key, child_a_signer) = ed25519::generate(&env);
+    if let Identifier::Ed25519(public_key) = child_a_key {
+        env.accounts().set_signer_weight(&child_a_account, &public_key, 1);
+    }
+    let child_b_account = env.accounts().generate_and_create();
+    let (child_b_key, child_b_signer) = ed25519::generate(&env);
+    if let Identifier::Ed25519(public_key) = child_b_key {
+        env.accounts().set_signer_weight(&child_b_account, &public_key, 1);
+    }
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    client.with_source_account(&admin).set_allow(&child_b_account, &payment_tkn_id, &100);
+
+    //after 1 day + 1000 seconds
+    //a third party relays child a's signed withdraw of 50
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+    let third_party_account = env.accounts().generate();
+    assert_eq!(0, client.get_nonce(&child_a_account));
+    let child_a_sig = sign_as_account(
+        &env,
+        &child_a_account,
+        &child_a_signer,
+        &contract_id,
+        symbol!("withdraw"),
+        (&child_a_account, &payment_tkn_id, 0_i128, 50_i128),
+    );
+    client
+        .with_source_account(&third_party_account)
+        .withdraw(&child_a_sig, &0, &child_a_account, &payment_tkn_id, &50);
+    assert_eq!(1, client.get_nonce(&child_a_account));
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 50);
+
+    //the same signature can't be replayed
+    assert_eq!(
+        client
+            .with_source_account(&third_party_account)
+            .try_withdraw(&child_a_sig, &0, &child_a_account, &payment_tkn_id, &50),
+        Err(Ok(Error::InvalidNonce))
+    );
+
+    //nor can child b sign child a's withdraw
+    let child_b_sig = sign_as_account(
+        &env,
+        &child_b_account,
+        &child_b_signer,
+        &contract_id,
+        symbol!("withdraw"),
+        (&child_a_account, &payment_tkn_id, 1_i128, 50_i128),
+    );
+    assert_eq!(
+        client
+            .with_source_account(&third_party_account)
+            .try_withdraw(&child_b_sig, &1, &child_a_account, &payment_tkn_id, &50),
+        Err(Ok(Error::InvalidInvoker))
+    );
+    assert_eq!(1, client.get_nonce(&child_a_account));
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 50);
+}
+
+// Make sure a signed withdraw can't be used to draw another amount than the one signed
+#[test]
+#[should_panic]
+fn test_invalid_signed_withdraw_panics_when_amount_is_not_the_signed_one() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a, signing with a key of its account
+    let child_a_account = env.accounts().generate_and_create();
+    let (child_a_key, child_a_signer) = ed25519::generate(&env);
+    if let Identifier::Ed25519(public_key) = child_a_key {
+        env.accounts().set_signer_weight(&child_a_account, &public_key, 1);
+    }
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //after 1 day + 1000 seconds
+    //child a signs a withdraw of 10, which is relayed as one of 100
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+    let child_a_sig = sign_as_account(
+        &env,
+        &child_a_account,
+        &child_a_signer,
+        &contract_id,
+        symbol!("withdraw"),
+        (&child_a_account, &payment_tkn_id, 0_i128, 10_i128),
+    );
+    client
+        .with_source_account(&admin)
+        .withdraw(&child_a_sig, &0, &child_a_account, &payment_tkn_id, &100);
+}
+
+// Verify children with a schedule of their own accrue on it, while the
+// others keep following the contract defaults
+#[test]
+fn test_valid_children_with_own_schedule() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a on the contract defaults
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 10;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //register child b on a weekly schedule starting in 1 day
+    let child_b_account = env.accounts().generate();
+    let child_b_allowance: i128 = 100;
+    client.with_source_account(&admin).set_sched(
+        &child_b_account,
+        &payment_tkn_id,
+        &(86400 * 7),
+        &(1669726146 + 86400),
+        &0,
+        &child_b_allowance,
+        &Calendar::Seconds,
+    );
+
+    assert_eq!(
+        Schedule {
+            start: 1669726146,
+            step: 86400,
+            end: 0,
+            amount: child_a_allowance,
+            calendar: Calendar::Seconds,
+        },
+        client.get_sched(&child_a_account, &payment_tkn_id)
+    );
+    assert_eq!(
+        Schedule {
+            start: 1669726146 + 86400,
+            step: 86400 * 7,
+            end: 0,
+            amount: child_b_allowance,
+            calendar: Calendar::Seconds,
+        },
+        client.get_sched(&child_b_account, &payment_tkn_id)
+    );
+    assert_eq!(child_b_allowance, client.get_allow(&child_b_account, &payment_tkn_id));
+
+    //after 8 days + 1000 seconds
+    //child a accrued 8 daily steps and child b a single weekly step
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 8) + 1000);
+    assert_eq!(80, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(100, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+
+    //changing the allowance of child b keeps its own schedule
+    client.with_source_account(&admin).set_allow(&child_b_account, &payment_tkn_id, &200);
+    assert_eq!(200, client.get_sched(&child_b_account, &payment_tkn_id).amount);
+    assert_eq!(86400 * 7, client.get_sched(&child_b_account, &payment_tkn_id).step);
+}
+
+// Make sure a child cannot withdraw before the start of its own schedule
+#[test]
+#[should_panic(expected = "Status(ContractError(9)")]
+fn test_invalid_withdraw_panics_when_before_child_start_period() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a on a schedule starting in 10 days
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_sched(
+        &child_a_account,
+        &payment_tkn_id,
+        &86400,
+        &(1669726146 + (86400 * 10)),
+        &0,
+        &child_a_allowance,
+        &Calendar::Seconds,
+    );
+
+    //after 2 days the contract defaults already started, but child a's schedule didn't
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 2));
+
+    let child_a_withdraw_amount: i128 = 50;
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+}
+
+// Verify raising an allowance mid-period doesn't grant back-pay for the past steps
+#[test]
+fn test_valid_allowance_increase_keeps_accrued_allowance() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //after 2 days + 1000 seconds
+    //child a accrued 2 steps at 100
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //the allowance is raised to 300, the accrued 200 stay the same
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &300);
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //after 4 days + 1000 seconds
+    //2 more steps were accrued at 300
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 4) + 1000));
+    assert_eq!(800, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+}
+
+// Verify lowering an allowance mid-period keeps what was already accrued
+#[test]
+fn test_valid_allowance_decrease_keeps_accrued_allowance() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //after 2 days + 1000 seconds
+    //child a accrued 2 steps at 100
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //the allowance is lowered to 50, the accrued 200 stay the same
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &50);
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //after 4 days + 1000 seconds
+    //2 more steps were accrued at 50
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 4) + 1000));
+    assert_eq!(300, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+}
+
+// Verify zeroing an allowance mid-period stops accrual but keeps what was already accrued
+#[test]
+fn test_valid_allowance_zeroed_keeps_accrued_allowance() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //after 2 days + 1000 seconds
+    //child a accrued 2 steps at 100
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //the allowance is zeroed, the accrued 200 stay the same
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &0);
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //after 4 days + 1000 seconds
+    //nothing else was accrued
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 4) + 1000));
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+}
+
+// Verify a child registered after the start period isn't paid for the steps before it
+#[test]
+fn test_valid_late_registration_has_no_back_pay() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //after 30 days + 1000 seconds child a is registered at 100 per day, and child b
+    //on a schedule of its own which started along with the contract
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 30) + 1000));
+    let child_a_account = env.accounts().generate();
+    let child_b_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    client.with_source_account(&admin).set_sched(
+        &child_b_account,
+        &payment_tkn_id,
+        &step_period,
+        &1669726146,
+        &end_period,
+        &100,
+        &Calendar::Seconds,
+    );
+    assert_eq!(0, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(0, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+
+    //both are paid from the next step on
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 31) + 1000));
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(100, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+}
+
+// Verify a suspended child doesn't accrue allowance while suspended
+// and keeps accruing after being unsuspended
+#[test]
+fn test_valid_suspended_child_accrual() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //after 1 day and a half child a is suspended
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + 86400 + 43200);
+    client.with_source_account(&admin).suspend(&child_a_account);
+    assert!(client.get_susp(&child_a_account));
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //after 3 days and a half child a is unsuspended, nothing was accrued in the meantime
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 3) + 43200);
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    client.with_source_account(&admin).unsuspend(&child_a_account);
+    assert!(!client.get_susp(&child_a_account));
+
+    //after 4 days + 1000 seconds
+    //child a was only active for 2 days + 1000 seconds
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 4) + 1000);
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+}
+
+// Verify no child accrues allowance while the contract is paused, and that
+// a pause overlapping a child suspension isn't discounted twice
+#[test]
+fn test_valid_paused_contract_accrual() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //register child b
+    let child_b_account = env.accounts().generate();
+    let child_b_allowance: i128 = 150;
+    client.with_source_account(&admin).set_allow(&child_b_account, &payment_tkn_id, &child_b_allowance);
+
+    //child b is suspended from day 1 to day 3
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + 86400);
+    client.with_source_account(&admin).suspend(&child_b_account);
+
+    //the contract is paused from day 2 to day 4
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 2));
+    client.with_source_account(&admin).pause();
+    assert_eq!(State::Paused, client.get_state());
+
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 3));
+    client.with_source_account(&admin).unsuspend(&child_b_account);
+
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 4));
+    client.with_source_account(&admin).resume();
+    assert_eq!(State::Started, client.get_state());
+
+    //after 5 days + 1000 seconds
+    //child a was active for 3 days + 1000 seconds
+    //child b was active for 2 days + 1000 seconds
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 5) + 1000);
+    assert_eq!(300, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(300, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+}
+
+// Make sure a child cannot withdraw while the contract is paused
+#[test]
+#[should_panic(expected = "Status(ContractError(12)")]
+fn test_invalid_withdraw_panics_when_contract_is_paused() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //after 1 day + 1000 seconds the contract is paused
+    //child a attempts to withdraw 50
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+    client.with_source_account(&admin).pause();
+
+    let child_a_withdraw_amount: i128 = 50;
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+}
+
+// Make sure a suspended child cannot withdraw
+#[test]
+#[should_panic(expected = "Status(ContractError(13)")]
+fn test_invalid_withdraw_panics_when_child_is_suspended() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //after 1 day + 1000 seconds child a is suspended
+    //child a attempts to withdraw 50
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+    client.with_source_account(&admin).suspend(&child_a_account);
+
+    let child_a_withdraw_amount: i128 = 50;
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+}
+
+// Make sure a removed child cannot withdraw anymore
+#[test]
+#[should_panic(expected = "Status(ContractError(10)")]
+fn test_invalid_withdraw_panics_when_child_is_removed() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //after 1 day + 1000 seconds child a is removed
+    //child a attempts to withdraw 50
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+    client.with_source_account(&admin).rm_child(&child_a_account);
+
+    let child_a_withdraw_amount: i128 = 50;
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+}
+
+// Make sure a removed child registered again isn't paid again for what it already withdrew
+#[test]
+fn test_valid_child_registered_again_after_removal() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //after 5 days + 1000 seconds child a withdraws 500 and is removed
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 5) + 1000));
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &500);
+    client.with_source_account(&admin).rm_child(&child_a_account);
+
+    //registered again, child a starts over from now on
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    assert_eq!(0, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(
+        Err(Ok(Error::InsufficientAllowance)),
+        client
+            .with_source_account(&child_a_account)
+            .try_withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &500)
+    );
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 500);
+
+    //and is paid again once the next day is over
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 6) + 1000));
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+}
+
+// Verify every state change publishes its event
+#[test]
+fn test_valid_events_published() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000000000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &500000000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //after 1 day + 1000 seconds
+    //child a withdraws 50 twice
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+
+    let child_a_withdraw_amount: i128 = 50;
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+
+    client.with_source_account(&admin).pause();
+
+    assert_eq!(
+        contract_events(&env, &contract_id),
+        vec![
+            &env,
+            (
+                (symbol!("allow"), symbol!("init")).into_val(&env),
+                (
+                    admin.clone(),
+                    payment_tkn_id.clone(),
+                    step_period,
+                    1669726146_u64,
+                    end_period,
+                )
+                    .into_val(&env),
+            ),
+            (
+                (symbol!("allow"), symbol!("set"), child_a_account.clone()).into_val(&env),
+                (payment_tkn_id.clone(), child_a_allowance, 1669726146_u64).into_val(&env),
+            ),
+            (
+                (symbol!("allow"), symbol!("withdraw"), child_a_account.clone()).into_val(&env),
+                (
+                    payment_tkn_id.clone(),
+                    50_i128,
+                    50_i128,
+                    1669726146_u64 + (86400 + 1000),
+                )
+                    .into_val(&env),
+            ),
+            (
+                (symbol!("allow"), symbol!("withdraw"), child_a_account.clone()).into_val(&env),
+                (
+                    payment_tkn_id.clone(),
+                    50_i128,
+                    100_i128,
+                    1669726146_u64 + (86400 + 1000),
+                )
+                    .into_val(&env),
+            ),
+            (
+                (symbol!("allow"), symbol!("pause")).into_val(&env),
+                (1669726146_u64 + (86400 + 1000)).into_val(&env),
+            ),
+        ]
+    );
+}
+
+// Verify sensitive actions only run once approved by the threshold of guardians,
+// while routine actions can be run by any single guardian
+#[test]
+fn test_valid_guardians_approval() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a while the admin is the only guardian
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+    assert_eq!(child_a_allowance, client.get_allow(&child_a_account, &payment_tkn_id));
+
+    //both parents and a grandparent become guardians, 2 of them must approve sensitive actions
+    let parent_b = env.accounts().generate();
+    let grandparent = env.accounts().generate();
+    let guardians = vec![&env, admin.clone(), parent_b.clone(), grandparent.clone()];
+    client.with_source_account(&admin).set_guards(&guardians, &2);
+    assert_eq!(
+        Guardians {
+            accounts: guardians.clone(),
+            threshold: 2,
+        },
+        client.get_guards()
+    );
+
+    //a single approval doesn't change the allowance
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &200);
+    assert_eq!(child_a_allowance, client.get_allow(&child_a_account, &payment_tkn_id));
+
+    //approving it twice with the same guardian still doesn't
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &200);
+    assert_eq!(child_a_allowance, client.get_allow(&child_a_account, &payment_tkn_id));
+
+    //a second guardian approval does
+    client.with_source_account(&parent_b).set_allow(&child_a_account, &payment_tkn_id, &200);
+    assert_eq!(200, client.get_allow(&child_a_account, &payment_tkn_id));
+
+    //so do the carryover policy and the claim window, as they can forfeit accrued allowance
+    client
+        .with_source_account(&admin)
+        .set_carry(&child_a_account, &payment_tkn_id, &Carryover::None);
+    client.with_source_account(&admin).set_claim(&86400);
+    assert_eq!(Carryover::Unlimited, client.get_carry(&child_a_account, &payment_tkn_id));
+    assert_eq!(0, client.get_claim());
+    client
+        .with_source_account(&grandparent)
+        .set_carry(&child_a_account, &payment_tkn_id, &Carryover::None);
+    client.with_source_account(&grandparent).set_claim(&86400);
+    assert_eq!(Carryover::None, client.get_carry(&child_a_account, &payment_tkn_id));
+    assert_eq!(86400, client.get_claim());
+
+    //routine actions only need a single guardian
+    client.with_source_account(&grandparent).suspend(&child_a_account);
+    assert!(client.get_susp(&child_a_account));
+
+    //rotating the guardians needs 2 approvals as well
+    let guardians_rotated = vec![&env, parent_b.clone(), grandparent.clone()];
+    client.with_source_account(&grandparent).set_guards(&guardians_rotated, &1);
+    assert_eq!(guardians, client.get_guards().accounts);
+    client.with_source_account(&parent_b).set_guards(&guardians_rotated, &1);
+    assert_eq!(
+        Guardians {
+            accounts: guardians_rotated,
+            threshold: 1,
+        },
+        client.get_guards()
+    );
+}
+
+// Make sure an account that isn't a guardian cannot manage the contract
+#[test]
+#[should_panic(expected = "Status(ContractError(4)")]
+fn test_invalid_set_allow_panics_when_invoker_is_not_a_guardian() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+    let client = ParentAllowanceClient::new(&env, contract_id.clone());
+
+    let admin = env.accounts().generate();
+    let step_period = 10;
+    let start_period = 10;
+    let end_period = 20;
+
+    client.initialize(
+        &admin,
+        &contract_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //a third party attempts to register child a
+    let third_party_account = env.accounts().generate();
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 10;
+
+    client.with_source_account(&third_party_account).set_allow(&child_a_account, &contract_id, &child_a_allowance);
+}
+
+// Verify the admin can be handed over to a new account, keeping the withdrawal
+// history and paying the allowances from the new admin from then on
+#[test]
+fn test_valid_admin_transfer() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+    let new_admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    //both the current and the new admin fund the contract
+    for parent in [admin.clone(), new_admin.clone()] {
+        payment_tkn.with_source_account(&admin).mint(
+            &Signature::Invoker,
+            &0,
+            &Identifier::Account(parent.clone()),
+            &1000000000,
+        );
+
+        payment_tkn.with_source_account(&parent).incr_allow(
+            &Signature::Invoker,
+            &0,
+            &Identifier::Contract(contract_id.clone()),
+            &500000000,
+        );
+    }
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 100;
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &child_a_allowance);
+
+    //after 1 day + 1000 seconds
+    //child a withdraws 50 from the current admin
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+
+    let child_a_withdraw_amount: i128 = 50;
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+
+    //the admin proposes the handover, valid for 1 day
+    let expiration = 1669726146 + (86400 * 2) + 1000;
+    client.with_source_account(&admin).prop_admin(&new_admin, &expiration);
+    assert_eq!(
+        AdminProp {
+            admin: new_admin.clone(),
+            expiration,
+        },
+        client.get_padmin()
+    );
+    assert_eq!(admin, client.get_admin());
+
+    //the new admin accepts it and takes the old admin's place among the guardians
+    client.with_source_account(&new_admin).acpt_admin();
+    assert_eq!(new_admin, client.get_admin());
+    assert_eq!(vec![&env, new_admin.clone()], client.get_guards().accounts);
+
+    //the withdrawal history is kept
+    assert_eq!(50, client.get_wthdr(&child_a_account, &payment_tkn_id));
+    assert_eq!(50, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //child a withdraws the remaining 50 from the new admin
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &child_a_withdraw_amount);
+    assert_eq!(
+        payment_tkn.balance(&Identifier::Account(admin.clone())),
+        1000000000 - child_a_withdraw_amount
+    );
+    assert_eq!(
+        payment_tkn.balance(&Identifier::Account(new_admin.clone())),
+        1000000000 - child_a_withdraw_amount
+    );
+}
+
+// Verify handing the admin role over to one of the guardians keeps
+// the threshold within reach of the remaining guardians
+#[test]
+fn test_valid_admin_transfer_to_a_guardian() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+    let parent_b = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //both parents must approve sensitive actions
+    client.with_source_account(&admin).set_guards(&vec![&env, admin.clone(), parent_b.clone()], &2);
+
+    //the admin hands its role over to the other parent
+    client.with_source_account(&admin).prop_admin(&parent_b, &(1669726146 + 86400));
+    client.with_source_account(&parent_b).acpt_admin();
+    assert_eq!(
+        Guardians {
+            accounts: vec![&env, parent_b.clone()],
+            threshold: 1,
+        },
+        client.get_guards()
+    );
+
+    //the remaining guardian can still run sensitive actions on its own
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&parent_b).set_allow(&child_a_account, &payment_tkn_id, &100);
+    assert_eq!(100, client.get_allow(&child_a_account, &payment_tkn_id));
+}
+
+// Make sure an admin handover cannot be accepted after it expires
+#[test]
+#[should_panic(expected = "Status(ContractError(15)")]
+fn test_invalid_admin_transfer_panics_when_proposal_is_expired() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+    let new_admin = env.accounts().generate();
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &contract_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //the admin proposes the handover, valid for 1 day
+    client.with_source_account(&admin).prop_admin(&new_admin, &(1669726146 + 86400));
+
+    //after 1 day + 1000 seconds the new admin attempts to accept it
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+    client.with_source_account(&new_admin).acpt_admin();
+}
+
+// Make sure only the proposed admin can accept an admin handover
+#[test]
+#[should_panic(expected = "Status(ContractError(4)")]
+fn test_invalid_admin_transfer_panics_when_invoker_is_not_the_proposed_admin() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+    let new_admin = env.accounts().generate();
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &contract_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //the admin proposes the handover, valid for 1 day
+    client.with_source_account(&admin).prop_admin(&new_admin, &(1669726146 + 86400));
+
+    //a third party attempts to accept it
+    let third_party_account = env.accounts().generate();
+    client.with_source_account(&third_party_account).acpt_admin();
+}
+
+// Verify a child can be paid in more than one token, each one
+// with its own allowance and withdrawals
+#[test]
+fn test_valid_multiple_tokens_withdraw() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+    let (reward_tkn_id, reward_tkn) =
+        create_token_contract(&env, &admin, "Reward Token", "RWD", 8);
+
+    for tkn in [&payment_tkn, &reward_tkn] {
+        tkn.with_source_account(&admin).mint(
+            &Signature::Invoker,
+            &0,
+            &Identifier::Account(admin.clone()),
+            &1000000000,
+        );
+
+        tkn.with_source_account(&admin).incr_allow(
+            &Signature::Invoker,
+            &0,
+            &Identifier::Contract(contract_id.clone()),
+            &500000000,
+        );
+    }
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //the reward token is added to the whitelist
+    client.with_source_account(&admin).add_token(&reward_tkn_id);
+    assert_eq!(
+        vec![&env, payment_tkn_id.clone(), reward_tkn_id.clone()],
+        client.get_tokens()
+    );
+
+    //register child a in both tokens
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    client.with_source_account(&admin).set_allow(&child_a_account, &reward_tkn_id, &30);
+
+    //after 2 days + 1000 seconds
+    //child a withdraws 150 in the payment token and 10 in the reward token
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(60, client.get_aval(&child_a_account, &reward_tkn_id).amount);
+
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &150);
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &reward_tkn_id, &10);
+
+    assert_eq!(150, client.get_wthdr(&child_a_account, &payment_tkn_id));
+    assert_eq!(10, client.get_wthdr(&child_a_account, &reward_tkn_id));
+    assert_eq!(50, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(50, client.get_aval(&child_a_account, &reward_tkn_id).amount);
+
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 150);
+    assert_eq!(reward_tkn.balance(&Identifier::Account(child_a_account.clone())), 10);
+
+    //the reward token can only leave the whitelist once child a doesn't hold anything in it
+    assert_eq!(
+        Err(Ok(Error::TokenInUse)),
+        client.with_source_account(&admin).try_rm_token(&reward_tkn_id)
+    );
+    client.with_source_account(&admin).rm_child(&child_a_account);
+    client.with_source_account(&admin).rm_token(&reward_tkn_id);
+    assert_eq!(vec![&env, payment_tkn_id.clone()], client.get_tokens());
+}
+
+// Make sure allowances cannot be paid in a token out of the whitelist
+#[test]
+#[should_panic(expected = "Status(ContractError(16)")]
+fn test_invalid_set_allow_panics_when_token_is_not_allowed() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+    let client = ParentAllowanceClient::new(&env, contract_id.clone());
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+    let (reward_tkn_id, _) =
+        create_token_contract(&env, &admin, "Reward Token", "RWD", 8);
+
+    let step_period = 10;
+    let start_period = 10;
+    let end_period = 20;
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //register child a in a token that was never added to the whitelist
+    let child_a_account = env.accounts().generate();
+    let child_a_allowance: i128 = 10;
+    client.with_source_account(&admin).set_allow(&child_a_account, &reward_tkn_id, &child_a_allowance);
+}
+
+// Verify that in escrow mode the allowances are paid out of the deposited funds
+// and only the surplus over what is owed to the children can be reclaimed
+#[test]
+fn test_valid_escrow_withdraw_and_reclaim() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    client.with_source_account(&admin).set_escrow(&true);
+    assert!(client.get_escrow());
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //the admin deposits 250 into the contract
+    client.with_source_account(&admin).deposit(&payment_tkn_id, &250);
+    assert_eq!(payment_tkn.balance(&Identifier::Contract(contract_id.clone())), 250);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(admin.clone())), 750);
+
+    //after 2 days + 1000 seconds the deposit covers what is available
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    let available = client.get_aval(&child_a_account, &payment_tkn_id);
+    assert_eq!(200, available.amount);
+    assert!(available.covered);
+
+    //child a withdraws 150, paid from the escrow
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &150);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 150);
+    assert_eq!(payment_tkn.balance(&Identifier::Contract(contract_id.clone())), 100);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(admin.clone())), 750);
+
+    //50 is still owed to child a, so only the other 50 can be reclaimed
+    client.with_source_account(&admin).reclaim(&payment_tkn_id, &50);
+    assert_eq!(payment_tkn.balance(&Identifier::Contract(contract_id.clone())), 50);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(admin.clone())), 800);
+
+    //one more day accrues an allowance the escrow no longer covers
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 3) + 1000));
+    let available = client.get_aval(&child_a_account, &payment_tkn_id);
+    assert_eq!(150, available.amount);
+    assert!(!available.covered);
+}
+
+// Make sure the funds owed to the children cannot be reclaimed from the escrow
+#[test]
+#[should_panic(expected = "Status(ContractError(18)")]
+fn test_invalid_reclaim_panics_when_funds_are_owed() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    client.with_source_account(&admin).set_escrow(&true);
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    client.with_source_account(&admin).deposit(&payment_tkn_id, &250);
+
+    //after 2 days + 1000 seconds 200 is owed to child a
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    client.with_source_account(&admin).reclaim(&payment_tkn_id, &100);
+}
+
+// Make sure deposits are only accepted in escrow mode
+#[test]
+#[should_panic(expected = "Status(ContractError(17)")]
+fn test_invalid_deposit_panics_when_escrow_is_disabled() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+    let client = ParentAllowanceClient::new(&env, contract_id.clone());
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 10;
+    let start_period = 10;
+    let end_period = 20;
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    client.with_source_account(&admin).deposit(&payment_tkn_id, &250);
+}
+
+// Make sure a withdraw beyond what the parent approved to the contract fails
+// with a dedicated error instead of inside the token contract
+#[test]
+#[should_panic(expected = "Status(ContractError(19)")]
+fn test_invalid_withdraw_panics_when_parent_approval_is_insufficient() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &100,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //after 2 days + 1000 seconds child a tries to withdraw 150 out of the 100 approved
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &150);
+}
+
+// Make sure a withdraw beyond the parent's balance fails with a dedicated error
+#[test]
+#[should_panic(expected = "Status(ContractError(20)")]
+fn test_invalid_withdraw_panics_when_parent_balance_is_insufficient() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &100,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //after 2 days + 1000 seconds child a tries to withdraw 150 out of the parent's 100
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &150);
+}
+
+// Verify the funding status totals what is owed to all children against the parent's funds
+#[test]
+fn test_valid_funding_status() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &250,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    let child_b_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_b_account, &payment_tkn_id, &50);
+
+    //after 2 days + 1000 seconds child a withdraws 150
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &150);
+
+    //50 is still owed to child a and 100 to child b
+    let funding = client.fund_stat(&payment_tkn_id);
+    assert_eq!(150, funding.owed);
+    assert_eq!(100, funding.approved);
+    assert_eq!(850, funding.balance);
+    assert_eq!(0, funding.escrowed);
+    assert!(!client.get_aval(&child_b_account, &payment_tkn_id).covered);
+}
+
+// Verify the registered children can be listed and summarized in a single call
+#[test]
+fn test_valid_children_list_and_summary() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    let child_b_account = env.accounts().generate();
+    let child_c_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    client.with_source_account(&admin).set_allow(&child_b_account, &payment_tkn_id, &50);
+    client.with_source_account(&admin).set_allow(&child_c_account, &payment_tkn_id, &10);
+    //updating an allowance doesn't register the child twice
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    client.with_source_account(&admin).rm_child(&child_c_account);
+
+    assert_eq!(
+        vec![&env, child_a_account.clone(), child_b_account.clone()],
+        client.list_child()
+    );
+
+    //after 2 days + 1000 seconds child a withdraws 150
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &150);
+
+    let summary = client.summary();
+    assert_eq!(2, summary.len());
+
+    let child_a = summary.get_unchecked(0).unwrap();
+    assert_eq!(child_a_account, child_a.child);
+    assert_eq!(payment_tkn_id, child_a.token);
+    assert_eq!(100, child_a.rate);
+    assert_eq!(150, child_a.withdrawn);
+    assert_eq!(50, child_a.available);
+    assert_eq!(
+        Schedule {
+            start: 1669726146,
+            step: 86400,
+            end: 0,
+            amount: 100,
+            calendar: Calendar::Seconds,
+        },
+        child_a.schedule
+    );
+
+    let child_b = summary.get_unchecked(1).unwrap();
+    assert_eq!(child_b_account, child_b.child);
+    assert_eq!(50, child_b.rate);
+    assert_eq!(0, child_b.withdrawn);
+    assert_eq!(100, child_b.available);
+}
+
+// Verify the entrypoints return typed errors clients can branch on
+#[test]
+fn test_valid_typed_errors() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let child_a_account = env.accounts().generate();
+
+    //nothing can be managed or read before the contract is initialized
+    assert_eq!(State::NotInititd, client.get_state());
+    assert_eq!(Err(Ok(Error::NotInitialized)), client.try_get_admin());
+    assert_eq!(
+        Err(Ok(Error::NotInitialized)),
+        client
+            .with_source_account(&admin)
+            .try_set_allow(&child_a_account, &payment_tkn_id, &100)
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+    assert_eq!(State::Initiated, client.get_state());
+
+    //nothing can be withdrawn before any allowance is set up
+    assert_eq!(
+        Err(Ok(Error::NotStarted)),
+        client
+            .with_source_account(&child_a_account)
+            .try_withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &10)
+    );
+
+    //only guardians can manage the contract
+    assert_eq!(
+        Err(Ok(Error::NotAuthorized)),
+        client
+            .with_source_account(&child_a_account)
+            .try_set_allow(&child_a_account, &payment_tkn_id, &100)
+    );
+
+    //unregistered children have nothing to read
+    assert_eq!(
+        Err(Ok(Error::ChildNotSet)),
+        client.try_get_allow(&child_a_account, &payment_tkn_id)
+    );
+
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    assert_eq!(State::Started, client.get_state());
+    assert_eq!(Ok(Ok(100)), client.try_get_allow(&child_a_account, &payment_tkn_id));
+}
+
+// Verify withdraws are held to the spending limits of the child
+#[test]
+fn test_valid_spending_limits() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //child a can withdraw up to 100 at once, 150 a day and once an hour
+    client
+        .with_source_account(&admin)
+        .set_limits(&child_a_account, &payment_tkn_id, &100, &86400, &150, &3600);
+    assert_eq!(
+        Limits {
+            per_draw: 100,
+            window: 86400,
+            per_window: 150,
+            interval: 3600,
+        },
+        client.get_limits(&child_a_account, &payment_tkn_id)
+    );
+
+    //after 5 days child a has 500 available, but only 100 can be withdrawn at once
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 5));
+    assert_eq!(
+        Err(Ok(Error::DrawLimitExceeded)),
+        client
+            .with_source_account(&child_a_account)
+            .try_withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &101)
+    );
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &100);
+
+    //half an hour later it's too soon to withdraw again
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 5) + 1800);
+    assert_eq!(
+        Err(Ok(Error::DrawTooSoon)),
+        client
+            .with_source_account(&child_a_account)
+            .try_withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &10)
+    );
+
+    //an hour later 50 more can be withdrawn, reaching the limit of the day
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 5) + 3600);
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &50);
+
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 5) + 7200);
+    assert_eq!(
+        Err(Ok(Error::WindowLimitExceeded)),
+        client
+            .with_source_account(&child_a_account)
+            .try_withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &10)
+    );
+
+    //a day after the first withdraw only the 50 withdrawn after it still count
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 6));
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &100);
+
+    //an hour later the 50 is out of the window too, leaving room for 50 more
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 6) + 3600);
+    assert_eq!(
+        Err(Ok(Error::WindowLimitExceeded)),
+        client
+            .with_source_account(&child_a_account)
+            .try_withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &60)
+    );
+
+    assert_eq!(250, client.get_wthdr(&child_a_account, &payment_tkn_id));
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 250);
+}
+
+// Make sure a withdraw above the per withdraw limit fails
+#[test]
+#[should_panic(expected = "Status(ContractError(21)")]
+fn test_invalid_withdraw_panics_when_above_draw_limit() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    client
+        .with_source_account(&admin)
+        .set_limits(&child_a_account, &payment_tkn_id, &50, &0, &0, &0);
+
+    //after 2 days + 1000 seconds child a tries to withdraw 150 at once
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &150);
+}
+
+// Verify the unclaimed allowance over the carryover policy of each child is forfeited
+#[test]
+fn test_valid_carryover_policies() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //child a keeps up to 150, child b up to 2 steps and child c only the current step
+    let child_a_account = env.accounts().generate();
+    let child_b_account = env.accounts().generate();
+    let child_c_account = env.accounts().generate();
+    for child in [&child_a_account, &child_b_account, &child_c_account] {
+        client.with_source_account(&admin).set_allow(child, &payment_tkn_id, &100);
+    }
+    assert_eq!(Carryover::Unlimited, client.get_carry(&child_a_account, &payment_tkn_id));
+
+    client
+        .with_source_account(&admin)
+        .set_carry(&child_a_account, &payment_tkn_id, &Carryover::Amount(150));
+    client
+        .with_source_account(&admin)
+        .set_carry(&child_b_account, &payment_tkn_id, &Carryover::Steps(2));
+    client
+        .with_source_account(&admin)
+        .set_carry(&child_c_account, &payment_tkn_id, &Carryover::None);
+    assert_eq!(Carryover::Amount(150), client.get_carry(&child_a_account, &payment_tkn_id));
+
+    //after 3 days + 1000 seconds 300 was accrued by each child
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 3) + 1000));
+    assert_eq!(150, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(200, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+    assert_eq!(100, client.get_aval(&child_c_account, &payment_tkn_id).amount);
+
+    //each child withdraws part of it, and what was over the policy doesn't come back
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &100);
+    client
+        .with_source_account(&child_b_account)
+        .withdraw(&Signature::Invoker, &0, &child_b_account, &payment_tkn_id, &150);
+    client
+        .with_source_account(&child_c_account)
+        .withdraw(&Signature::Invoker, &0, &child_c_account, &payment_tkn_id, &30);
+    assert_eq!(50, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(50, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+    assert_eq!(70, client.get_aval(&child_c_account, &payment_tkn_id).amount);
+
+    //one more day accrues 100 on top of what was left, up to each policy, in a
+    //later transaction with a fresh budget
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 4) + 1000));
+    env.budget().reset();
+    assert_eq!(150, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(150, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+    assert_eq!(100, client.get_aval(&child_c_account, &payment_tkn_id).amount);
+
+    //the funds owed follow the policies as well
+    assert_eq!(400, client.fund_stat(&payment_tkn_id).owed);
+}
+
+// Verify a child can request withdraws for the parent to approve, as an advance if needed
+#[test]
+fn test_valid_withdraw_requests() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //after 2 days + 1000 seconds child a requests 150 for a book, which is approved
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    let book = Bytes::from_slice(&env, b"book");
+    let book_id = client.with_source_account(&child_a_account).request(
+        &Signature::Invoker,
+        &0,
+        &child_a_account,
+        &payment_tkn_id,
+        &150,
+        &book,
+    );
+    assert_eq!(ReqStatus::Pending, client.get_req(&book_id).status);
+
+    client.with_source_account(&admin).aprv_req(&book_id, &false);
+    assert_eq!(ReqStatus::Approved, client.get_req(&book_id).status);
+    assert_eq!(50, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //then 300 for a bike, more than what is available, so it's paid as an advance
+    let bike_id = client.with_source_account(&child_a_account).request(
+        &Signature::Invoker,
+        &0,
+        &child_a_account,
+        &payment_tkn_id,
+        &300,
+        &Bytes::from_slice(&env, b"bike"),
+    );
+    assert_eq!(
+        Err(Ok(Error::InsufficientAllowance)),
+        client.with_source_account(&admin).try_aprv_req(&bike_id, &false)
+    );
+    client.with_source_account(&admin).aprv_req(&bike_id, &true);
+    assert_eq!(ReqStatus::Advanced, client.get_req(&bike_id).status);
+    assert_eq!(-250, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(450, client.get_wthdr(&child_a_account, &payment_tkn_id));
+
+    //and a game, which is rejected
+    let game_id = client.with_source_account(&child_a_account).request(
+        &Signature::Invoker,
+        &0,
+        &child_a_account,
+        &payment_tkn_id,
+        &50,
+        &Bytes::from_slice(&env, b"game"),
+    );
+    client.with_source_account(&admin).rjct_req(&game_id);
+    assert_eq!(ReqStatus::Rejected, client.get_req(&game_id).status);
+    assert_eq!(
+        Err(Ok(Error::RequestNotPending)),
+        client.with_source_account(&admin).try_aprv_req(&game_id, &false)
+    );
+
+    //the advance is paid back by the next 3 days
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 5) + 1000));
+    assert_eq!(50, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 450);
+
+    let request = client.get_req(&book_id);
+    assert_eq!(child_a_account, request.child);
+    assert_eq!(150, request.amount);
+    assert_eq!(book, request.memo);
+}
+
+// Make sure only the admin can approve a request
+#[test]
+#[should_panic(expected = "Status(ContractError(4)")]
+fn test_invalid_request_approval_panics_when_invoker_is_not_the_admin() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //after 2 days + 1000 seconds child a requests 150 and tries to approve it itself
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    let id = client.with_source_account(&child_a_account).request(
+        &Signature::Invoker,
+        &0,
+        &child_a_account,
+        &payment_tkn_id,
+        &150,
+        &Bytes::from_slice(&env, b"book"),
+    );
+    client.with_source_account(&child_a_account).aprv_req(&id, &false);
+}
+
+// Verify a child can withdraw in advance up to its limit, paying it back with the next steps
+#[test]
+fn test_valid_advance_withdraw() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //child a can withdraw up to 150 in advance
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    assert_eq!(0, client.get_advnc(&child_a_account, &payment_tkn_id));
+    client.with_source_account(&admin).set_advnc(&child_a_account, &payment_tkn_id, &150);
+    assert_eq!(150, client.get_advnc(&child_a_account, &payment_tkn_id));
+
+    //after 1 day + 1000 seconds child a has 100 available, so 260 is over its limit
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+    assert_eq!(
+        Err(Ok(Error::InsufficientAllowance)),
+        client
+            .with_source_account(&child_a_account)
+            .try_withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &260)
+    );
+
+    //but 200 is within it
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &200);
+    assert_eq!(-100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(100, client.get_debt(&child_a_account, &payment_tkn_id));
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 200);
+
+    //the next day pays it back
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    assert_eq!(0, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(0, client.get_debt(&child_a_account, &payment_tkn_id));
+
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 3) + 1000));
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(0, client.get_debt(&child_a_account, &payment_tkn_id));
+}
+
+// Verify the reward of a chore done is paid on top of the allowance
+#[test]
+fn test_valid_chore_rewards() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //child a is assigned two chores
+    let car_id = client.with_source_account(&admin).add_chore(
+        &child_a_account,
+        &payment_tkn_id,
+        &30,
+        &Bytes::from_slice(&env, b"wash car"),
+    );
+    let lawn_id = client.with_source_account(&admin).add_chore(
+        &child_a_account,
+        &payment_tkn_id,
+        &50,
+        &Bytes::from_slice(&env, b"mow lawn"),
+    );
+
+    //after 1 day + 1000 seconds the car is washed
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+    client.with_source_account(&admin).done_chore(&car_id);
+    assert_eq!(130, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(
+        Err(Ok(Error::ChoreDone)),
+        client.with_source_account(&admin).try_done_chore(&car_id)
+    );
+
+    let chores = client.get_chores(&child_a_account);
+    assert_eq!(2, chores.len());
+    assert!(chores.get_unchecked(0).unwrap().done);
+    assert!(!chores.get_unchecked(1).unwrap().done);
+    assert_eq!(50, client.get_chore(&lawn_id).reward);
+
+    //child a withdraws the allowance along with the reward
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &130);
+    assert_eq!(0, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 130);
+
+    //the next day the lawn is mowed as well
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    client.with_source_account(&admin).done_chore(&lawn_id);
+    assert_eq!(150, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //once 2 guardians must approve, a single one can't credit a reward
+    let parent_b = env.accounts().generate();
+    client
+        .with_source_account(&admin)
+        .set_guards(&vec![&env, admin.clone(), parent_b.clone()], &2);
+    let room_id = client.with_source_account(&parent_b).add_chore(
+        &child_a_account,
+        &payment_tkn_id,
+        &20,
+        &Bytes::from_slice(&env, b"clean room"),
+    );
+    client.with_source_account(&parent_b).done_chore(&room_id);
+    assert!(!client.get_chore(&room_id).done);
+    assert_eq!(150, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //the second approval does
+    client.with_source_account(&admin).done_chore(&room_id);
+    assert!(client.get_chore(&room_id).done);
+    assert_eq!(170, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+}
+
+// Verify chore rewards not withdrawn yet are kept in full over the carryover policy,
+// while the ones already withdrawn don't raise it
+#[test]
+fn test_valid_chore_rewards_over_carryover() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //child a only keeps the current step of its allowance
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    client
+        .with_source_account(&admin)
+        .set_carry(&child_a_account, &payment_tkn_id, &Carryover::None);
+
+    //a chore is done right away
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + 1000);
+    let car_id = client.with_source_account(&admin).add_chore(
+        &child_a_account,
+        &payment_tkn_id,
+        &50,
+        &Bytes::from_slice(&env, b"wash car"),
+    );
+    client.with_source_account(&admin).done_chore(&car_id);
+    assert_eq!(50, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //after 3 days + 1000 seconds only a step of the allowance is kept, but the whole reward is
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 3) + 1000));
+    assert_eq!(150, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //the allowance is withdrawn first, then part of the reward
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &120);
+    assert_eq!(30, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //a day later the next step is added to what is left of the reward
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 4) + 1000));
+    assert_eq!(130, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //and the part of the reward withdrawn doesn't let more of the allowance carry over
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 6) + 1000));
+    assert_eq!(130, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+}
+
+// Verify a child can save towards goals, released along with the parent's match
+// once their target or unlock timestamp is reached
+#[test]
+fn test_valid_savings_goals() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //child a saves for a bike worth 300, which the parent tops up by 50%
+    let bike_id = client.with_source_account(&child_a_account).add_goal(
+        &Signature::Invoker,
+        &0,
+        &child_a_account,
+        &payment_tkn_id,
+        &Bytes::from_slice(&env, b"bike"),
+        &300,
+        &0,
+    );
+    client.with_source_account(&admin).match_goal(&bike_id, &5000);
+    assert_eq!(
+        Err(Ok(Error::InvalidArguments)),
+        client.with_source_account(&admin).try_match_goal(&bike_id, &10001)
+    );
+
+    //and for a trip worth 1000, unlocked after 4 days anyway
+    let trip_id = client.with_source_account(&child_a_account).add_goal(
+        &Signature::Invoker,
+        &0,
+        &child_a_account,
+        &payment_tkn_id,
+        &Bytes::from_slice(&env, b"trip"),
+        &1000,
+        &(1669726146 + (86400 * 4)),
+    );
+
+    //after 2 days + 1000 seconds child a locks 150 for the bike, which is still short of it
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    client
+        .with_source_account(&child_a_account)
+        .save_goal(&Signature::Invoker, &0, &bike_id, &150);
+    assert_eq!(50, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(150, client.get_goal(&bike_id).saved);
+    assert_eq!(275, client.fund_stat(&payment_tkn_id).owed);
+    assert_eq!(
+        Err(Ok(Error::GoalLocked)),
+        client
+            .with_source_account(&child_a_account)
+            .try_wthdr_goal(&Signature::Invoker, &0, &bike_id)
+    );
+    assert_eq!(
+        Err(Ok(Error::InsufficientAllowance)),
+        client
+            .with_source_account(&child_a_account)
+            .try_save_goal(&Signature::Invoker, &0, &trip_id, &51)
+    );
+
+    //the next day 50 more takes the bike to its target with the match
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 3) + 1000));
+    client
+        .with_source_account(&child_a_account)
+        .save_goal(&Signature::Invoker, &0, &bike_id, &50);
+    client
+        .with_source_account(&child_a_account)
+        .save_goal(&Signature::Invoker, &0, &trip_id, &40);
+    client
+        .with_source_account(&child_a_account)
+        .wthdr_goal(&Signature::Invoker, &0, &bike_id);
+    assert!(client.get_goal(&bike_id).released);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 300);
+    assert_eq!(
+        Err(Ok(Error::GoalReleased)),
+        client
+            .with_source_account(&child_a_account)
+            .try_save_goal(&Signature::Invoker, &0, &bike_id, &10)
+    );
+
+    //the trip is still locked until its unlock timestamp
+    assert_eq!(
+        Err(Ok(Error::GoalLocked)),
+        client
+            .with_source_account(&child_a_account)
+            .try_wthdr_goal(&Signature::Invoker, &0, &trip_id)
+    );
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 4));
+    client
+        .with_source_account(&child_a_account)
+        .wthdr_goal(&Signature::Invoker, &0, &trip_id);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 340);
+
+    assert_eq!(2, client.get_goals(&child_a_account).len());
+    assert_eq!(240, client.get_wthdr(&child_a_account, &payment_tkn_id));
+
+    //once 2 guardians must approve, a single one can't set the match of a goal
+    let parent_b = env.accounts().generate();
+    client
+        .with_source_account(&admin)
+        .set_guards(&vec![&env, admin.clone(), parent_b.clone()], &2);
+    let game_id = client.with_source_account(&child_a_account).add_goal(
+        &Signature::Invoker,
+        &0,
+        &child_a_account,
+        &payment_tkn_id,
+        &Bytes::from_slice(&env, b"game"),
+        &60,
+        &0,
+    );
+    client.with_source_account(&parent_b).match_goal(&game_id, &2500);
+    assert_eq!(0, client.get_goal(&game_id).match_bps);
+
+    //the second approval does
+    client.with_source_account(&admin).match_goal(&game_id, &2500);
+    assert_eq!(2500, client.get_goal(&game_id).match_bps);
+}
+
+// Make sure savings goals can't be used to get around the spending limits
+#[test]
+fn test_valid_goal_release_within_spending_limits() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &10000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &10000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //child a can withdraw up to 100 at once and 100 a day
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    client
+        .with_source_account(&admin)
+        .set_limits(&child_a_account, &payment_tkn_id, &100, &86400, &100, &0);
+
+    //after 10 days + 1000 seconds child a can't withdraw the 1000 available at once
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 10) + 1000));
+    assert_eq!(
+        Err(Ok(Error::DrawLimitExceeded)),
+        client
+            .with_source_account(&child_a_account)
+            .try_withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &1000)
+    );
+
+    //nor most of them by saving them into a goal reached right away
+    let cash_id = client.with_source_account(&child_a_account).add_goal(
+        &Signature::Invoker,
+        &0,
+        &child_a_account,
+        &payment_tkn_id,
+        &Bytes::from_slice(&env, b"cash"),
+        &1,
+        &0,
+    );
+    client
+        .with_source_account(&child_a_account)
+        .save_goal(&Signature::Invoker, &0, &cash_id, &800);
+    assert_eq!(
+        Err(Ok(Error::DrawLimitExceeded)),
+        client
+            .with_source_account(&child_a_account)
+            .try_wthdr_goal(&Signature::Invoker, &0, &cash_id)
+    );
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 0);
+
+    //a goal within the limits is paid along with the parent's match, which is exempt
+    let book_id = client.with_source_account(&child_a_account).add_goal(
+        &Signature::Invoker,
+        &0,
+        &child_a_account,
+        &payment_tkn_id,
+        &Bytes::from_slice(&env, b"book"),
+        &1,
+        &0,
+    );
+    client
+        .with_source_account(&child_a_account)
+        .save_goal(&Signature::Invoker, &0, &book_id, &10);
+    client.with_source_account(&admin).match_goal(&book_id, &10000);
+    client
+        .with_source_account(&child_a_account)
+        .wthdr_goal(&Signature::Invoker, &0, &book_id);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 20);
+
+    //and what was saved counts towards the limit of the day
+    assert_eq!(
+        Err(Ok(Error::WindowLimitExceeded)),
+        client
+            .with_source_account(&child_a_account)
+            .try_withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &91)
+    );
+}
+
+// Verify the interest compounds in fixed point, only over a positive balance
+#[test]
+fn test_valid_interest_calculation() {
+    //each step earns interest over the balance before adding its amount
+    assert_eq!(Some((1000, 0)), accrue_with_interest(1000, 0, 100, 0, 1000));
+    assert_eq!(Some((1000, 0)), accrue_with_interest(1000, 0, 0, 1, 0));
+    assert_eq!(Some((1200, 0)), accrue_with_interest(1000, 0, 100, 1, 1000));
+    assert_eq!(Some((1420, 0)), accrue_with_interest(1000, 0, 100, 2, 1000));
+    assert_eq!(Some((2000, 0)), accrue_with_interest(1000, 0, 0, 1, 10000));
+    assert_eq!(None, accrue_with_interest(i128::MAX, 0, 1, 1, 0));
+
+    //only the balance is rounded down, keeping the fraction of a unit earned
+    assert_eq!(
+        Some((9999, INTEREST_SCALE / 10000 * 9999)),
+        accrue_with_interest(9999, 0, 0, 1, 1)
+    );
+    assert_eq!(
+        Some((10000, 999_899_990_000_000_000)),
+        accrue_with_interest(9999, INTEREST_SCALE / 10000 * 9999, 0, 1, 1)
+    );
+
+    //a balance in debt doesn't earn interest until it's paid back
+    assert_eq!(Some((-1000, 0)), accrue_with_interest(-1000, 0, 0, 5, 100));
+    assert_eq!(Some((0, 0)), accrue_with_interest(-200, 0, 100, 2, 1000));
+    assert_eq!(Some((210, 0)), accrue_with_interest(-200, 0, 100, 4, 1000));
+
+    //compounding takes the same time however many steps there are, and carrying the
+    //fraction along, splitting the steps anywhere gives the same balance
+    assert_eq!(Some((11051, 653_926_032_325_500_000)), accrue_with_interest(10000, 0, 0, 1000, 1));
+    let (balance, fraction) = accrue_with_interest(0, 0, 1, 1234, 10).unwrap();
+    assert_eq!(
+        accrue_with_interest(0, 0, 1, 2000, 10).unwrap().0,
+        accrue_with_interest(balance, fraction, 1, 766, 10).unwrap().0
+    );
+    assert_eq!(None, accrue_with_interest(1, 0, 0, u64::MAX, 1));
+}
+
+// Verify the unspent allowance of a child earns interest at each step
+#[test]
+fn test_valid_allowance_interest() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //child a earns 10% per day over what it didn't withdraw
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    assert_eq!(0, client.get_intrst(&child_a_account, &payment_tkn_id));
+    client.with_source_account(&admin).set_intrst(&child_a_account, &payment_tkn_id, &1000);
+    assert_eq!(1000, client.get_intrst(&child_a_account, &payment_tkn_id));
+
+    //nothing was left unspent over the first day
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //the second day pays 10 of interest over those 100
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 2) + 1000));
+    assert_eq!(210, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &110);
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 110);
+
+    //only the 100 left earn interest the third day
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 3) + 1000));
+    assert_eq!(210, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //the interest already earned is kept once it's removed
+    client.with_source_account(&admin).set_intrst(&child_a_account, &payment_tkn_id, &0);
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 4) + 1000));
+    assert_eq!(310, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //once 2 guardians must approve, a single one can't change the rate
+    let parent_b = env.accounts().generate();
+    client
+        .with_source_account(&admin)
+        .set_guards(&vec![&env, admin.clone(), parent_b.clone()], &2);
+    client.with_source_account(&parent_b).set_intrst(&child_a_account, &payment_tkn_id, &500);
+    assert_eq!(0, client.get_intrst(&child_a_account, &payment_tkn_id));
+    client.with_source_account(&admin).set_intrst(&child_a_account, &payment_tkn_id, &500);
+    assert_eq!(500, client.get_intrst(&child_a_account, &payment_tkn_id));
+}
+
+// Verify the interest earned doesn't depend on when the allowance was checkpointed
+#[test]
+fn test_valid_interest_independent_of_checkpoints() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 60; // 1 minute in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //both children earn 1 per minute and 0.1% per minute over it
+    let child_a_account = env.accounts().generate();
+    let child_b_account = env.accounts().generate();
+    for child_account in [&child_a_account, &child_b_account] {
+        client.with_source_account(&admin).set_allow(child_account, &payment_tkn_id, &1);
+        client.with_source_account(&admin).set_intrst(child_account, &payment_tkn_id, &10);
+    }
+
+    //only child b has its allowance checkpointed halfway, without changing it
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (60 * 1000 + 30));
+    client.with_source_account(&admin).set_allow(&child_b_account, &payment_tkn_id, &1);
+
+    env.budget().reset();
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (60 * 2000 + 30));
+    let available = client.get_aval(&child_a_account, &payment_tkn_id).amount;
+    assert!(available > 2000);
+    assert_eq!(available, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+}
+
+// Verify an interest grown too big to be calculated can still be removed
+#[test]
+fn test_valid_interest_removed_after_overflow() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //child a doubles what it didn't withdraw every day
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    client.with_source_account(&admin).set_intrst(&child_a_account, &payment_tkn_id, &10000);
+
+    //after 200 days it's too big to be calculated
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + ((86400 * 200) + 1000));
+    assert_eq!(
+        Err(Ok(Error::Overflow)),
+        client.try_get_aval(&child_a_account, &payment_tkn_id)
+    );
+
+    //removing the interest recalculates those days without it
+    client.with_source_account(&admin).set_intrst(&child_a_account, &payment_tkn_id, &0);
+    assert_eq!(0, client.get_intrst(&child_a_account, &payment_tkn_id));
+    assert_eq!(20000, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+}
+
+// Make sure the allowance already withdrawn can't be withdrawn again
+#[test]
+#[should_panic(expected = "Status(ContractError(7)")]
+fn test_invalid_repeated_withdraw_panics_when_allowance_was_withdrawn() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //after 1 day + 1000 seconds child a withdraws its 100, then tries to withdraw them again
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 + 1000));
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &100);
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &100);
+}
+
+// Verify random sequences of allowance changes, time advances and withdraws never let
+// a child withdraw more than it accrued plus its advance limit, and that the token
+// balances always match what was withdrawn
+#[test]
+fn test_valid_withdraw_invariants_with_random_sequences() {
+    for (seed, advance_limit) in [
+        (0x2545f4914f6cdd1d_u64, 0_i128),
+        (0x9e3779b97f4a7c15, 50),
+        (0xd1b54a32d192ed03, 150),
+    ] {
+        check_withdraw_invariants(seed, advance_limit);
+    }
+}
+
+fn check_withdraw_invariants(seed: u64, advance_limit: i128) {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let start = 1669726146;
+    let mut now = start;
+    let mut client = updates_contract_time(&env, contract_id.clone(), now);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let funds = 1000000;
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &funds,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &funds,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //the model tracks, for each child, its allowance and the totals accrued and withdrawn
+    let children = [env.accounts().generate(), env.accounts().generate()];
+    let mut allowances = [100_i128, 100];
+    let mut accrued = [0_i128, 0];
+    let mut withdrawn = [0_i128, 0];
+    for child in children.iter() {
+        client.with_source_account(&admin).set_allow(child, &payment_tkn_id, &100);
+        client.with_source_account(&admin).set_advnc(child, &payment_tkn_id, &advance_limit);
+    }
+
+    let mut seed = seed;
+    for _ in 0..60 {
+        let child = (next_random(&mut seed) % 2) as usize;
+        match next_random(&mut seed) % 3 {
+            0 => {
+                let allowance = (next_random(&mut seed) % 200) as i128 + 1;
+                client.with_source_account(&admin).set_allow(
+                    &children[child],
+                    &payment_tkn_id,
+                    &allowance,
+                );
+                allowances[child] = allowance;
+            }
+            1 => {
+                let later = now + next_random(&mut seed) % (step_period * 2);
+                let steps = ((later - start) / step_period - (now - start) / step_period) as i128;
+                for i in 0..2 {
+                    accrued[i] += steps * allowances[i];
+                }
+                now = later;
+                client = updates_contract_time(&env, contract_id.clone(), now);
+            }
+            _ => {
+                let amount = (next_random(&mut seed) % 300) as i128 + 1;
+                let result = client.with_source_account(&children[child]).try_withdraw(
+                    &Signature::Invoker,
+                    &0,
+                    &children[child],
+                    &payment_tkn_id,
+                    &amount,
+                );
+                if withdrawn[child] + amount <= accrued[child] + advance_limit {
+                    assert_eq!(Ok(Ok(())), result);
+                    withdrawn[child] += amount;
+                } else {
+                    assert_eq!(Err(Ok(Error::InsufficientAllowance)), result);
+                }
+            }
+        }
+
+        //the totals and balances match the model after every step
+        for i in 0..2 {
+            assert!(withdrawn[i] <= accrued[i] + advance_limit);
+            assert_eq!(withdrawn[i], client.get_wthdr(&children[i], &payment_tkn_id));
+            assert_eq!(
+                accrued[i] - withdrawn[i],
+                client.get_aval(&children[i], &payment_tkn_id).amount
+            );
+            assert_eq!(
+                withdrawn[i],
+                payment_tkn.balance(&Identifier::Account(children[i].clone()))
+            );
+        }
+        assert_eq!(
+            funds - withdrawn[0] - withdrawn[1],
+            payment_tkn.balance(&Identifier::Account(admin.clone()))
+        );
+        env.budget().reset();
+    }
+}
+
+// Make sure the contract can't be initialized with an end period before its start
+#[test]
+#[should_panic(expected = "Status(ContractError(33)")]
+fn test_invalid_contract_initialize_panics_when_end_period_is_not_after_start() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 1669800000; // starts in future date
+    let end_period = 1669800000; // ends right when it starts
+
+    client.initialize(
+        &admin,
+        &contract_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+}
+
+// Verify negative amounts and invalid periods are rejected, and that amounts
+// too big to be calculated fail instead of wrapping around
+#[test]
+fn test_valid_amount_and_period_validation() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 1669800000; // starts in future date
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    let child_a_account = env.accounts().generate();
+    assert_eq!(
+        Err(Ok(Error::NegativeAmount)),
+        client.with_source_account(&admin).try_set_allow(&child_a_account, &payment_tkn_id, &-100)
+    );
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+
+    //nothing is available before the start
+    assert_eq!(0, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //a schedule must end after it starts
+    assert_eq!(
+        Err(Ok(Error::InvalidPeriod)),
+        client.with_source_account(&admin).try_set_sched(
+            &child_a_account,
+            &payment_tkn_id,
+            &step_period,
+            &1669900000,
+            &1669800000,
+            &100,
+            &Calendar::Seconds,
+        )
+    );
+
+    //only positive amounts can be withdrawn
+    client = updates_contract_time(&env, contract_id.clone(), 1669800000 + (86400 + 1000));
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    for amount in [0, -100] {
+        assert_eq!(
+            Err(Ok(Error::NegativeAmount)),
+            client
+                .with_source_account(&child_a_account)
+                .try_withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &amount)
+        );
+    }
+
+    //an allowance accruing past the i128 bounds fails with an overflow
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &i128::MAX);
+    client = updates_contract_time(&env, contract_id.clone(), 1669800000 + ((86400 * 3) + 1000));
+    assert_eq!(
+        Err(Ok(Error::Overflow)),
+        client.try_get_aval(&child_a_account, &payment_tkn_id)
+    );
+}
+
+// Verify calendar steps fall on their day across month lengths and leap years
+#[test]
+fn test_valid_calendar_steps() {
+    let steps = |calendar: Calendar, from: u64, to: u64| {
+        count_calendar_steps(&calendar, to) - count_calendar_steps(&calendar, from)
+    };
+
+    //on the 1st of every month, from 2024-01-15 up to the end of february and into march
+    assert_eq!(1, steps(Calendar::Monthly(1), 1705276800, 1709251199));
+    assert_eq!(2, steps(Calendar::Monthly(1), 1705276800, 1709251200));
+
+    //on the 31st, falling on the 28th of february in 2023 and on the 29th in 2024
+    assert_eq!(1, steps(Calendar::Monthly(31), 1672531200, 1677542399));
+    assert_eq!(2, steps(Calendar::Monthly(31), 1672531200, 1677542400));
+    assert_eq!(1, steps(Calendar::Monthly(31), 1704067200, 1709078400));
+    assert_eq!(2, steps(Calendar::Monthly(31), 1704067200, 1709164800));
+
+    //2100 isn't a leap year, while 2000 is
+    assert_eq!(1, steps(Calendar::Monthly(29), 4107455999, 4107456000));
+    assert_eq!(0, steps(Calendar::Monthly(29), 951695999, 951696000));
+    assert_eq!(1, steps(Calendar::Monthly(29), 951695999, 951782400));
+
+    //every saturday, the first one being 1970-01-03 as the epoch fell on a thursday
+    assert_eq!(1, count_calendar_steps(&Calendar::Weekly(4), 0));
+    assert_eq!(0, steps(Calendar::Weekly(6), 0, 172799));
+    assert_eq!(1, steps(Calendar::Weekly(6), 172799, 172800));
+    assert_eq!(2, steps(Calendar::Weekly(6), 1708905600, 1709942400));
+
+    assert_eq!(0, steps(Calendar::Seconds, 0, 1709942400));
+}
+
+// Verify children on calendar schedules accrue on a day of every month or week,
+// skipping the ones falling while the contract is paused
+#[test]
+fn test_valid_calendar_schedules() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger on 2024-01-15
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1705276800);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, _) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //child a is paid on the 1st of every month, starting right away
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_sched(
+        &child_a_account,
+        &payment_tkn_id,
+        &0,
+        &0,
+        &0,
+        &100,
+        &Calendar::Monthly(1),
+    );
+    assert_eq!(
+        Err(Ok(Error::InvalidPeriod)),
+        client.with_source_account(&admin).try_set_sched(
+            &child_a_account,
+            &payment_tkn_id,
+            &0,
+            &0,
+            &0,
+            &100,
+            &Calendar::Weekly(8),
+        )
+    );
+
+    //child b is paid every saturday, starting on monday 2024-02-26
+    let child_b_account = env.accounts().generate();
+    client.with_source_account(&admin).set_sched(
+        &child_b_account,
+        &payment_tkn_id,
+        &0,
+        &1708905600,
+        &0,
+        &100,
+        &Calendar::Weekly(6),
+    );
+
+    //2024-02-01
+    client = updates_contract_time(&env, contract_id.clone(), 1706745600 + 1000);
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(0, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+
+    //2024-02-29, no other month started yet
+    client = updates_contract_time(&env, contract_id.clone(), 1709164800);
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //2024-03-01
+    client = updates_contract_time(&env, contract_id.clone(), 1709251200);
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(0, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+
+    //saturday 2024-03-02 up to friday 2024-03-08, then saturday 2024-03-09
+    client = updates_contract_time(&env, contract_id.clone(), 1709337600);
+    assert_eq!(100, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+    client = updates_contract_time(&env, contract_id.clone(), 1709856000);
+    assert_eq!(100, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+    client = updates_contract_time(&env, contract_id.clone(), 1709942400);
+    assert_eq!(200, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+
+    //paused from 2024-03-15 to 2024-04-05, so april's step is skipped
+    client = updates_contract_time(&env, contract_id.clone(), 1710460800);
+    client.with_source_account(&admin).pause();
+    client = updates_contract_time(&env, contract_id.clone(), 1712275200);
+    client.with_source_account(&admin).resume();
+
+    //2024-05-01
+    client = updates_contract_time(&env, contract_id.clone(), 1714521600);
+    assert_eq!(300, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+}
+
+// Verify a streaming allowance can be withdrawn in part before its step is completed,
+// and that switching modes neither pays the step in progress twice nor loses it
+#[test]
+fn test_valid_streaming_allowance() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 0; // no end date
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //child a streams 100 per day
+    let child_a_account = env.accounts().generate();
+    client.with_source_account(&admin).set_allow(&child_a_account, &payment_tkn_id, &100);
+    assert!(!client.get_stream(&child_a_account, &payment_tkn_id));
+    client.with_source_account(&admin).set_stream(&child_a_account, &payment_tkn_id, &true);
+    assert!(client.get_stream(&child_a_account, &payment_tkn_id));
+
+    //a third of a day streams 33, rounded down
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + 28800);
+    assert_eq!(33, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &33);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 33);
+
+    //the rounding doesn't add up, so a full day streams the whole 100
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + 57600);
+    assert_eq!(33, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + 86400);
+    assert_eq!(67, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //switching out of streaming half way through a day takes back its 50,
+    //paid in full once the day is completed
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + 86400 + 43200);
+    assert_eq!(117, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    client.with_source_account(&admin).set_stream(&child_a_account, &payment_tkn_id, &false);
+    assert_eq!(67, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 2));
+    assert_eq!(167, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    //switching back in a quarter through a day streams its 25 right away
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 2) + 21600);
+    assert_eq!(167, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    client.with_source_account(&admin).set_stream(&child_a_account, &payment_tkn_id, &true);
+    assert_eq!(192, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+}
+
+// Verify steps paid at the start of their period are available right away, and
+// that the schedule still pays the same total once it ends
+#[test]
+fn test_valid_payout_timing() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ParentAllowance);
+
+    //set the initial state for the ledger
+    let mut client = updates_contract_time(&env, contract_id.clone(), 1669726146);
+
+    let admin = env.accounts().generate();
+
+    let (payment_tkn_id, payment_tkn) =
+        create_token_contract(&env, &admin, "USD Coin", "USDC", 8);
+
+    payment_tkn.with_source_account(&admin).mint(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Account(admin.clone()),
+        &1000,
+    );
+
+    payment_tkn.with_source_account(&admin).incr_allow(
+        &Signature::Invoker,
+        &0,
+        &Identifier::Contract(contract_id.clone()),
+        &1000,
+    );
+
+    let step_period = 86400; // 1 day in seconds
+    let start_period = 0; // starts right away
+    let end_period = 1669726146 + (86400 * 3); // ends after 3 days
+
+    client.initialize(
+        &admin,
+        &payment_tkn_id,
+        &step_period,
+        &start_period,
+        &end_period,
+    );
+
+    //child a and b are paid at the start of each day, child c at its end
+    let child_a_account = env.accounts().generate();
+    let child_b_account = env.accounts().generate();
+    let child_c_account = env.accounts().generate();
+    for child in [&child_a_account, &child_b_account, &child_c_account] {
+        client.with_source_account(&admin).set_allow(child, &payment_tkn_id, &100);
+    }
+    assert_eq!(Payout::End, client.get_payout(&child_a_account, &payment_tkn_id));
+    for child in [&child_a_account, &child_b_account] {
+        client.with_source_account(&admin).set_payout(child, &payment_tkn_id, &Payout::Start);
+    }
+    assert_eq!(Payout::Start, client.get_payout(&child_a_account, &payment_tkn_id));
+
+    //child d is paid at the start of each day too, on a schedule ending half way through one
+    let child_d_account = env.accounts().generate();
+    client.with_source_account(&admin).set_sched(
+        &child_d_account,
+        &payment_tkn_id,
+        &step_period,
+        &0,
+        &(1669726146 + (86400 * 2) + 43200),
+        &100,
+        &Calendar::Seconds,
+    );
+    client.with_source_account(&admin).set_payout(&child_d_account, &payment_tkn_id, &Payout::Start);
+
+    //the first day is available right away
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(0, client.get_aval(&child_c_account, &payment_tkn_id).amount);
+    client
+        .with_source_account(&child_a_account)
+        .withdraw(&Signature::Invoker, &0, &child_a_account, &payment_tkn_id, &100);
+    assert_eq!(payment_tkn.balance(&Identifier::Account(child_a_account.clone())), 100);
+
+    //switching child a back to the end of each day half way through the second one
+    //takes it back until the day is over, in a later transaction with a fresh budget
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + 86400 + 43200);
+    env.budget().reset();
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    client.with_source_account(&admin).set_payout(&child_a_account, &payment_tkn_id, &Payout::End);
+    assert_eq!(0, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 2));
+    assert_eq!(100, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(300, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+    assert_eq!(200, client.get_aval(&child_c_account, &payment_tkn_id).amount);
+
+    //once the schedules are over, every child got its 3 days
+    client = updates_contract_time(&env, contract_id.clone(), 1669726146 + (86400 * 5));
+    assert_eq!(200, client.get_aval(&child_a_account, &payment_tkn_id).amount);
+    assert_eq!(300, client.get_aval(&child_b_account, &payment_tkn_id).amount);
+    assert_eq!(300, client.get_aval(&child_c_account, &payment_tkn_id).amount);
+    assert_eq!(300, client.get_aval(&child_d_account, &payment_tkn_id).amount);
 }